@@ -0,0 +1,45 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use clap::Args;
+
+use crate::{
+    cli::{CliOptions, MergePolicy, traits::CommandRunner},
+    error::N34Result,
+};
+
+#[derive(Args, Debug)]
+pub struct MergePolicyArgs {
+    /// The minimum number of maintainers approvals of the current tip
+    #[arg(long, default_value = "0")]
+    min_approvals:        u8,
+    /// Block the merge while a maintainer requests changes to the current tip
+    #[arg(long)]
+    no_requested_changes: bool,
+}
+
+impl CommandRunner for MergePolicyArgs {
+    const NEED_SIGNER: bool = false;
+
+    async fn run(self, mut options: CliOptions) -> N34Result<()> {
+        let policy = MergePolicy {
+            min_approvals:        self.min_approvals,
+            no_requested_changes: self.no_requested_changes,
+        };
+        options.config.merge_policy = (!policy.is_empty()).then_some(policy);
+        options.config.dump()
+    }
+}
@@ -18,6 +18,8 @@
 mod bunker;
 /// `config keyring` subcommand
 mod keyring;
+/// `config merge-policy` subcommand
+mod merge_policy;
 /// `config nip07` subcommand
 mod nip07;
 /// `config pow` subcommand
@@ -29,6 +31,7 @@ use clap::Subcommand;
 
 use self::bunker::BunkerArgs;
 use self::keyring::KeyringArgs;
+use self::merge_policy::MergePolicyArgs;
 use self::nip07::Nip07Args;
 use self::pow::PowArgs;
 use self::relays::RelaysArgs;
@@ -51,10 +54,13 @@ pub enum ConfigSubcommands {
     /// Controls the NIP-07 browser signer proxy, turning it on or off, and
     /// configures the `ip:port` address.
     Nip07(Nip07Args),
+    /// Sets the reviews that patches and pull requests need before merging
+    /// them. Without options the policy is removed.
+    MergePolicy(MergePolicyArgs),
 }
 
 impl CommandRunner for ConfigSubcommands {
     async fn run(self, options: CliOptions) -> N34Result<()> {
-        crate::run_command!(self, options, & Pow Relays Bunker Keyring Nip07)
+        crate::run_command!(self, options, & Pow Relays Bunker Keyring Nip07 MergePolicy)
     }
 }
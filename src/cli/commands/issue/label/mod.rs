@@ -0,0 +1,42 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+/// `issue label add` subcommand
+mod add;
+/// `issue label remove` subcommand
+mod remove;
+
+use clap::Subcommand;
+
+use self::add::AddArgs;
+use self::remove::RemoveArgs;
+use crate::{
+    cli::{CliOptions, traits::CommandRunner},
+    error::N34Result,
+};
+
+#[derive(Subcommand, Debug)]
+pub enum LabelSubcommands {
+    /// Add labels to an issue
+    Add(AddArgs),
+    /// Remove labels from an issue
+    Remove(RemoveArgs),
+}
+
+impl CommandRunner for LabelSubcommands {
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        crate::run_command!(self, options, & Add Remove)
+    }
+}
@@ -21,9 +21,9 @@ use clap::Args;
 use crate::{
     cli::{
         CliOptions,
-        common_commands,
+        common_commands::{self, ListFilters},
         traits::CommandRunner,
-        types::{EntityType, NaddrOrSet},
+        types::{EntityType, NaddrOrSet, UserOrMe},
     },
     error::N34Result,
 };
@@ -37,10 +37,16 @@ pub struct ListArgs {
     ///
     /// If omitted, looks for a `nostr-address` file.
     #[arg(value_name = "NADDR-NIP05-OR-SET", value_delimiter = ',')]
-    naddrs: Option<Vec<NaddrOrSet>>,
-    /// Maximum number of issues to list
+    naddrs:   Option<Vec<NaddrOrSet>>,
+    /// Maximum number of issues in a page
     #[arg(long, default_value = "15")]
-    limit:  NonZeroUsize,
+    limit:    NonZeroUsize,
+    /// Only list the issues assigned to this user, a public key or `me` for
+    /// the signer
+    #[arg(long, value_name = "NPUB-OR-ME")]
+    assignee: Option<UserOrMe>,
+    #[command(flatten)]
+    filters:  ListFilters,
 }
 
 impl CommandRunner for ListArgs {
@@ -51,6 +57,8 @@ impl CommandRunner for ListArgs {
             options,
             self.naddrs,
             self.limit.into(),
+            self.filters,
+            self.assignee,
         )
         .await
     }
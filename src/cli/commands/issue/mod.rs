@@ -14,8 +14,12 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
 
+/// `issue assign` subcommand
+mod assign;
 /// `issue close` subcommand
 mod close;
+/// `issue label` subcommands
+mod label;
 /// `issue list` subcommand
 mod list;
 /// `issue new` subcommand
@@ -24,6 +28,10 @@ mod new;
 mod reopen;
 /// `issue resolve` subcommand
 mod resolve;
+/// `issue retitle` subcommand
+mod retitle;
+/// `issue unassign` subcommand
+mod unassign;
 /// `issue view` subcommand
 mod view;
 
@@ -32,11 +40,15 @@ use std::fmt;
 use clap::Subcommand;
 use nostr::event::Kind;
 
+use self::assign::AssignArgs;
 use self::close::CloseArgs;
+use self::label::LabelSubcommands;
 use self::list::ListArgs;
 use self::new::NewArgs;
 use self::reopen::ReopenArgs;
 use self::resolve::ResolveArgs;
+use self::retitle::RetitleArgs;
+use self::unassign::UnassignArgs;
 use self::view::ViewArgs;
 use super::{CliOptions, CommandRunner};
 use crate::error::{N34Error, N34Result};
@@ -58,6 +70,17 @@ pub enum IssueSubcommands {
     Resolve(ResolveArgs),
     /// List issues.
     List(ListArgs),
+    /// Add or remove issue labels
+    Label {
+        #[command(subcommand)]
+        subcommands: LabelSubcommands,
+    },
+    /// Change the subject of an issue
+    Retitle(RetitleArgs),
+    /// Assign users to an issue
+    Assign(AssignArgs),
+    /// Unassign users from an issue
+    Unassign(UnassignArgs),
 }
 
 /// Possible states for a Git issue
@@ -147,6 +170,6 @@ impl TryFrom<Kind> for IssueStatus {
 
 impl CommandRunner for IssueSubcommands {
     async fn run(self, options: CliOptions) -> N34Result<()> {
-        crate::run_command!(self, options, & New View Reopen Close Resolve List)
+        crate::run_command!(self, options, Label & New View Reopen Close Resolve List Retitle Assign Unassign)
     }
 }
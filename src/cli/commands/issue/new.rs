@@ -22,6 +22,7 @@ use crate::{
     cli::{
         CliOptions,
         CommandRunner,
+        issue_templates,
         traits::{OptionNaddrOrSetVecExt, RelayOrSetVecExt},
         types::NaddrOrSet,
     },
@@ -54,22 +55,27 @@ pub struct NewArgs {
         long = "repo",
         value_delimiter = ','
     )]
-    naddrs:  Option<Vec<NaddrOrSet>>,
+    naddrs:   Option<Vec<NaddrOrSet>>,
     /// Markdown content for the issue. Cannot be used together with the
     /// `--editor` flag.
     #[arg(short, long, group = "issue-content")]
-    content: Option<String>,
+    content:  Option<String>,
     /// Opens the user's default editor to write issue content. The first line
     /// will be used as the issue subject.
     #[arg(short, long, group = "issue-subject", group = "issue-content")]
-    editor:  bool,
+    editor:   bool,
     /// The issue subject. Cannot be used together with the `--editor` flag.
     #[arg(long, group = "issue-subject")]
-    subject: Option<String>,
+    subject:  Option<String>,
     /// Labels for the issue. Can be specified as arguments (-l bug) or hashtags
     /// in content (#bug).
     #[arg(short, long)]
-    label:   Vec<String>,
+    label:    Vec<String>,
+    /// The issue template to pre-fill the editor and the labels with. Cannot
+    /// be used together with the `--editor`, `--subject` and `--content`
+    /// options.
+    #[arg(short, long, group = "issue-subject", conflicts_with = "content")]
+    template: Option<String>,
 }
 
 impl CommandRunner for NewArgs {
@@ -91,7 +97,30 @@ impl CommandRunner for NewArgs {
             .add_relays(&utils::add_read_relays(relays_list.as_ref()))
             .await;
 
-        let (subject, content) = utils::subject_and_body(self.subject, self.content, ".md")?;
+        let template = if self.editor || self.template.is_some() {
+            issue_templates::pick_template(
+                issue_templates::merge_templates(
+                    issue_templates::local_templates(&issue_templates::templates_dir()?)?,
+                    issue_templates::fetch_repos_templates(&client, &coordinates).await?,
+                ),
+                self.template.as_deref(),
+                !options.non_interactive,
+            )?
+        } else {
+            None
+        };
+
+        let (subject, content) = match &template {
+            Some(template) => {
+                utils::editor_subject_and_body(Some(&template.editor_content()), ".md")?
+            }
+            None => utils::subject_and_body(self.subject, self.content, ".md")?,
+        };
+        let labels = utils::dedup(
+            self.label
+                .into_iter()
+                .chain(template.into_iter().flat_map(|t| t.labels)),
+        );
 
         let content_details = if let Some(content) = &content {
             Some(client.parse_content(content).await)
@@ -103,7 +132,7 @@ impl CommandRunner for NewArgs {
             coordinates.as_slice(),
             content.unwrap_or_default(),
             Some(subject),
-            self.label,
+            labels,
         )?
         .dedup_tags()
         .pow(options.pow.unwrap_or_default())
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
 
+use std::num::NonZeroUsize;
+
 use clap::Args;
 
 use crate::{
@@ -38,16 +40,31 @@ pub struct ViewArgs {
         long = "repo",
         value_delimiter = ','
     )]
-    naddrs:   Option<Vec<NaddrOrSet>>,
+    naddrs:        Option<Vec<NaddrOrSet>>,
     /// The issue id to view it
-    issue_id: NostrEvent,
+    issue_id:      NostrEvent,
+    /// Show the comments threads, the default
+    #[arg(long, overrides_with = "no_comments")]
+    comments:      bool,
+    /// Hide the comments threads
+    #[arg(long, overrides_with = "comments")]
+    no_comments:   bool,
+    /// The page of the comments threads to show, each page has 10 top-level
+    /// comments with their replies
+    #[arg(long, value_name = "PAGE", default_value = "1")]
+    comments_page: NonZeroUsize,
 }
 
 impl CommandRunner for ViewArgs {
     const NEED_SIGNER: bool = false;
 
     async fn run(self, options: CliOptions) -> N34Result<()> {
-        crate::cli::common_commands::view_pr_issue::<false>(options, self.naddrs, self.issue_id)
-            .await
+        crate::cli::common_commands::view_pr_issue::<false>(
+            options,
+            self.naddrs,
+            self.issue_id,
+            (!self.no_comments).then_some(self.comments_page.get()),
+        )
+        .await
     }
 }
@@ -67,30 +67,34 @@ const BUNKER_TIMEOUT: Duration = Duration::from_secs(60 * 3);
 pub struct CliOptions {
     /// Your Nostr secret key
     #[arg(short, long, group = "signer")]
-    pub secret_key: Option<SecretKey>,
+    pub secret_key:      Option<SecretKey>,
     /// NIP-46 bunker url used for signing events
     #[arg(short, long, group = "signer", value_parser = parsers::parse_bunker_url)]
-    pub bunker_url: Option<NostrConnectURI>,
+    pub bunker_url:      Option<NostrConnectURI>,
     /// Enables signing events using the browser's NIP-07 extension. Listens on
     /// `127.0.0.1:51034`.
     #[arg(short = '7', long, group = "signer")]
-    pub nip07:      bool,
+    pub nip07:           bool,
     /// Fallbacks relay to write and read from it. Multiple relays can be
     /// passed, separated by commas.
     #[arg(short, long, value_delimiter = ',')]
-    pub relays:     Vec<RelayOrSet>,
+    pub relays:          Vec<RelayOrSet>,
     /// Proof of Work difficulty when creatring events
     #[arg(long, value_name = "DIFFICULTY")]
-    pub pow:        Option<u8>,
+    pub pow:             Option<u8>,
+    /// Never prompt for input. Git pushes fail instead of asking for the
+    /// credentials, useful for scripts.
+    #[arg(long)]
+    pub non_interactive: bool,
     /// Config path [default: `$XDG_CONFIG_HOME` or `$HOME/.config`]
     #[arg(long, value_name = "PATH", default_value = DEFAULT_FALLBACK_PATH,
          hide_default_value = true, value_parser = parsers::parse_config_path
      )]
-    pub config:     CliConfig,
+    pub config:          CliConfig,
     /// The state of options. Some values that are used by them but should not
     /// be entered via the CLI
     #[arg(skip)]
-    pub state:      OptionsState,
+    pub state:           OptionsState,
 }
 
 /// N34 commands
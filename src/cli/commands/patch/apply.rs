@@ -55,31 +55,36 @@ pub struct ApplyArgs {
 impl CommandRunner for ApplyArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
         crate::cli::common_commands::patch_pr_status_command::<{ EntityType::Patch as u8 }>(
-            options,
+            &options,
             self.patch_id,
             self.naddrs,
             PatchPrStatus::MergedApplied,
             Some(either::Either::Right(self.applied_commits)),
             self.applied_patches.into_event_ids(),
-            |patch_status| {
-                if patch_status.is_merged_or_applied() {
-                    return Err(N34Error::InvalidStatus(
-                        "You can't apply an already applied patch".to_owned(),
-                    ));
-                }
-
-                if patch_status.is_closed() {
-                    return Err(N34Error::InvalidStatus(
-                        "You can't apply a closed patch".to_owned(),
-                    ));
-                }
-
-                if patch_status.is_drafted() {
-                    return Err(N34Error::InvalidStatus("Cannot apply a draft".to_owned()));
-                }
-                Ok(())
-            },
+            None,
+            None,
+            check_can_apply,
         )
         .await
     }
 }
+
+/// Checks that a patch with the given status can be marked as applied
+pub fn check_can_apply(patch_status: &PatchPrStatus) -> N34Result<()> {
+    if patch_status.is_merged_or_applied() {
+        return Err(N34Error::InvalidStatus(
+            "You can't apply an already applied patch".to_owned(),
+        ));
+    }
+
+    if patch_status.is_closed() {
+        return Err(N34Error::InvalidStatus(
+            "You can't apply a closed patch".to_owned(),
+        ));
+    }
+
+    if patch_status.is_drafted() {
+        return Err(N34Error::InvalidStatus("Cannot apply a draft".to_owned()));
+    }
+    Ok(())
+}
@@ -0,0 +1,139 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use clap::Args;
+
+use super::GitPatch;
+use crate::{
+    cli::{
+        CliOptions,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{NaddrOrSet, NostrEvent},
+    },
+    error::{N34Error, N34Result},
+    git_utils,
+    nostr_utils::{
+        NostrClient,
+        traits::{GitPatchUtils, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+#[derive(Debug, Args)]
+pub struct CheckoutArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs:   Option<Vec<NaddrOrSet>>,
+    /// The branch to create. Defaults to a name derived from the root patch
+    /// subject.
+    #[arg(short, long)]
+    branch:   Option<String>,
+    /// The commit or branch to start the new branch from.
+    #[arg(long, value_name = "REV", default_value = "HEAD")]
+    base:     String,
+    /// Abort `git am` and delete the created branch if the patches don't
+    /// apply cleanly, instead of leaving a resumable `git am` session.
+    #[arg(long)]
+    cleanup:  bool,
+    /// The root patch id to checkout
+    patch_id: NostrEvent,
+}
+
+impl CommandRunner for CheckoutArgs {
+    const NEED_SIGNER: bool = false;
+
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        git_utils::ensure_work_tree()?;
+
+        let naddrs = utils::naddrs_or_file(
+            self.naddrs.flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client
+            .add_relays(
+                &[
+                    naddrs.extract_relays(),
+                    self.patch_id.relays,
+                    client
+                        .fetch_repos(&naddrs.into_coordinates())
+                        .await?
+                        .extract_relays(),
+                ]
+                .concat(),
+            )
+            .await;
+
+        let root_patch = client.fetch_patch(self.patch_id.event_id).await?;
+        if !root_patch.is_root_patch() {
+            return Err(N34Error::NotRootPatch);
+        }
+
+        let patches = client
+            .fetch_ordered_patch_series(root_patch)
+            .await?
+            .iter()
+            .map(GitPatch::from_event)
+            .collect::<N34Result<Vec<_>>>()?;
+
+        let branch = match self.branch {
+            Some(branch) => branch,
+            None => super::patch_file_name(&patches[0].subject)?,
+        };
+        let original_ref = git_utils::current_ref()?;
+
+        tracing::info!("Creating the branch `{branch}` from `{}`", self.base);
+        git_utils::checkout_new_branch(&branch, &self.base)?;
+
+        let mbox = patches
+            .iter()
+            .map(|p| p.inner.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if let Err(err) = git_utils::am(mbox) {
+            if self.cleanup {
+                tracing::info!("Cleaning up the branch `{branch}`");
+                git_utils::abort_am(&original_ref, &branch)?;
+            } else {
+                println!(
+                    "The patches don't apply cleanly. Resolve the conflicts then run `git am \
+                     --continue`, or run `git am --abort` to give up"
+                );
+            }
+            return Err(err);
+        }
+
+        println!(
+            "Applied {} patch{} to the branch `{branch}`",
+            patches.len(),
+            if patches.len() >= 2 { "es" } else { "" }
+        );
+
+        Ok(())
+    }
+}
@@ -46,27 +46,33 @@ pub struct CloseArgs {
 impl CommandRunner for CloseArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
         crate::cli::common_commands::patch_pr_status_command::<{ EntityType::Patch as u8 }>(
-            options,
+            &options,
             self.patch_id,
             self.naddrs,
             PatchPrStatus::Closed,
             None,
             Vec::new(),
-            |patch_status| {
-                if patch_status.is_closed() {
-                    return Err(N34Error::InvalidStatus(
-                        "You can't close an already closed patch".to_owned(),
-                    ));
-                }
-
-                if patch_status.is_merged_or_applied() {
-                    return Err(N34Error::InvalidStatus(
-                        "You can't close a merged/applied patch".to_owned(),
-                    ));
-                }
-                Ok(())
-            },
+            None,
+            None,
+            check_can_close,
         )
         .await
     }
 }
+
+/// Checks if the patch can be closed, it must not be closed or
+/// merged/applied.
+pub fn check_can_close(patch_status: &PatchPrStatus) -> N34Result<()> {
+    if patch_status.is_closed() {
+        return Err(N34Error::InvalidStatus(
+            "You can't close an already closed patch".to_owned(),
+        ));
+    }
+
+    if patch_status.is_merged_or_applied() {
+        return Err(N34Error::InvalidStatus(
+            "You can't close a merged/applied patch".to_owned(),
+        ));
+    }
+    Ok(())
+}
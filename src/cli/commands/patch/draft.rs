@@ -46,12 +46,14 @@ pub struct DraftArgs {
 impl CommandRunner for DraftArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
         crate::cli::common_commands::patch_pr_status_command::<{ EntityType::Patch as u8 }>(
-            options,
+            &options,
             self.patch_id,
             self.naddrs,
             PatchPrStatus::Draft,
             None,
             Vec::new(),
+            None,
+            None,
             |patch_status| {
                 if patch_status.is_drafted() {
                     return Err(N34Error::InvalidStatus(
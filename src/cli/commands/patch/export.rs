@@ -0,0 +1,206 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use clap::Args;
+use futures::future;
+use nostr::{
+    event::{Event, EventId},
+    key::PublicKey,
+    nips::nip19::ToBech32,
+};
+
+use super::GitPatch;
+use crate::{
+    cli::{
+        CliOptions,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{NaddrOrSet, NostrEvent},
+    },
+    error::{N34Error, N34Result},
+    mbox::{self, Message},
+    nostr_utils::{
+        NostrClient,
+        traits::{GitPatchUtils, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+/// The domain of the message IDs made from the patch event IDs, and of the
+/// addresses of the authors without a NIP-05.
+const NOSTR_MAIL_DOMAIN: &str = "nostr.invalid";
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs:   Option<Vec<NaddrOrSet>>,
+    /// The mbox file to write the patch series to
+    #[arg(long, value_name = "PATH")]
+    mbox:     PathBuf,
+    /// The patch to export, the series of its root is exported
+    #[arg(value_name = "EVENT-ID")]
+    patch_id: NostrEvent,
+}
+
+impl CommandRunner for ExportArgs {
+    const NEED_SIGNER: bool = false;
+
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        let naddrs = utils::naddrs_or_file(
+            self.naddrs.flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client
+            .add_relays(&[naddrs.extract_relays(), self.patch_id.relays].concat())
+            .await;
+        let repos = client.fetch_repos(&naddrs.into_coordinates()).await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let patch = client.fetch_patch(self.patch_id.event_id).await?;
+        let root = match patch.patch_root() {
+            Some(root_id) => client.fetch_patch(root_id).await?,
+            None => patch,
+        };
+        let series = client.fetch_ordered_patch_series(root).await?;
+
+        let authors = utils::dedup(series.iter().map(|e| e.pubkey));
+        let addresses = future::join_all(authors.iter().map(|author| {
+            async {
+                let metadata = client.fetch_metadata(*author).await.unwrap_or_default();
+                author_address(
+                    *author,
+                    metadata.display_name.or(metadata.name).as_deref(),
+                    metadata.nip05.as_deref(),
+                )
+            }
+        }))
+        .await;
+        let addresses = authors.into_iter().zip(addresses).collect();
+
+        fs::write(&self.mbox, series_to_mbox(&series, &addresses)?)?;
+        println!(
+            "Exported {} patches to `{}`",
+            series.len(),
+            self.mbox.display()
+        );
+
+        Ok(())
+    }
+}
+
+/// Returns the `From:` header of the Nostr user, with its NIP-05 as the email
+/// address. Users without a NIP-05 get an address from their `npub`.
+pub fn author_address(pubkey: PublicKey, name: Option<&str>, nip05: Option<&str>) -> String {
+    let npub = pubkey.to_bech32().expect("Infallible");
+    let email = nip05
+        .filter(|nip05| nip05.contains('@'))
+        .map(str::to_owned)
+        .unwrap_or_else(|| format!("{npub}@{NOSTR_MAIL_DOMAIN}"));
+
+    mbox::encode_address(name.unwrap_or(&npub), &email)
+}
+
+/// Converts the ordered patch series to a threaded mbox. The `In-Reply-To` and
+/// `References` headers follow the `e` reply chain of the patches, and the
+/// authors are mapped to the `From:` headers by the given addresses. The git
+/// author is kept as an in-body `From:` if it's different, like `git
+/// send-email` does.
+pub fn series_to_mbox(
+    series: &[Event],
+    addresses: &HashMap<PublicKey, String>,
+) -> N34Result<String> {
+    let mut message_ids: HashMap<EventId, String> = HashMap::new();
+    let mut references: HashMap<EventId, Vec<String>> = HashMap::new();
+    let mut messages = Vec::with_capacity(series.len());
+
+    for event in series {
+        let patch = GitPatch::from_event(event)?;
+        let message = Message::parse(&patch.inner).map_err(N34Error::InvalidPatch)?;
+        let (_, content) = mbox::in_body_headers(&message.body);
+
+        let message_id = patch
+            .message_id
+            .clone()
+            .unwrap_or_else(|| event_message_id(&event.id));
+        let from = addresses
+            .get(&event.pubkey)
+            .cloned()
+            .unwrap_or_else(|| author_address(event.pubkey, None, None));
+        let date = patch.date.clone().unwrap_or_else(|| {
+            chrono::DateTime::from_timestamp(event.created_at.as_u64() as i64, 0)
+                .unwrap_or_default()
+                .to_rfc2822()
+        });
+
+        let mut headers = vec![
+            ("From".to_owned(), from.clone()),
+            ("Date".to_owned(), date),
+            ("Subject".to_owned(), mbox::encode_header(&patch.subject)),
+            ("Message-ID".to_owned(), format!("<{message_id}>")),
+        ];
+
+        if let Some(parent) = event.patch_reply_to().or(event.patch_root()) {
+            let parent_message_id = message_ids
+                .get(&parent)
+                .cloned()
+                .unwrap_or_else(|| event_message_id(&parent));
+            let mut patch_references = references.get(&parent).cloned().unwrap_or_default();
+            patch_references.push(parent_message_id.clone());
+
+            headers.push(("In-Reply-To".to_owned(), format!("<{parent_message_id}>")));
+            headers.push((
+                "References".to_owned(),
+                patch_references
+                    .iter()
+                    .map(|id| format!("<{id}>"))
+                    .collect::<Vec<_>>()
+                    .join("\n "),
+            ));
+            references.insert(event.id, patch_references);
+        }
+
+        let body = match patch.author.as_deref() {
+            Some(author) if mbox::decode_header(&from) != author => {
+                format!("From: {author}\n\n{content}")
+            }
+            _ => content.to_owned(),
+        };
+
+        messages.push(Message::new_plain_text(patch.commit.clone(), headers, body).format(true));
+        message_ids.insert(event.id, message_id);
+    }
+
+    Ok(messages.join("\n"))
+}
+
+/// Returns the message ID of the patch event, without the angle brackets
+fn event_message_id(event_id: &EventId) -> String {
+    format!("{}@{NOSTR_MAIL_DOMAIN}", event_id.to_hex())
+}
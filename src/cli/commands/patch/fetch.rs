@@ -14,15 +14,12 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
 
-use std::{fs, ops::Deref, path::PathBuf, str::FromStr};
+use std::{collections::HashSet, fs, ops::Deref, path::PathBuf};
 
 use clap::Args;
-use nostr::{
-    event::{Kind, TagKind},
-    filter::Filter,
-    nips::nip19::ToBech32,
-};
+use nostr::event::Event;
 
+use super::GitPatch;
 use crate::{
     cli::{
         CliOptions,
@@ -30,9 +27,10 @@ use crate::{
         types::{NaddrOrSet, NostrEvent},
     },
     error::{N34Error, N34Result},
+    git_utils,
     nostr_utils::{
         NostrClient,
-        traits::{NaddrsUtils, ReposUtils},
+        traits::{GitPatchUtils, NaddrsUtils, ReposUtils},
         utils,
     },
 };
@@ -50,13 +48,17 @@ pub struct FetchArgs {
         long = "repo",
         value_delimiter = ','
     )]
-    naddrs:   Option<Vec<NaddrOrSet>>,
+    naddrs:    Option<Vec<NaddrOrSet>>,
     /// Directory for saving patches. Defaults to the current directory. Use `-`
     /// for stdout output.
     #[arg(short, long, value_name = "PATH")]
-    output:   Option<PathBuf>,
+    output:    Option<PathBuf>,
+    /// Don't verify that the patches recreate the commits in their `commit`
+    /// tags.
+    #[arg(long)]
+    no_verify: bool,
     /// The patch id to fetch it
-    patch_id: NostrEvent,
+    patch_id:  NostrEvent,
 }
 
 impl CommandRunner for FetchArgs {
@@ -89,46 +91,30 @@ impl CommandRunner for FetchArgs {
             )
             .await;
 
-        let root_patch = client
-            .fetch_event(
-                Filter::new()
-                    .id(self.patch_id.event_id)
-                    .kind(Kind::GitPatch),
-            )
-            .await?
-            .ok_or(N34Error::CanNotFoundPatch)?;
-
-        if !root_patch
-            .tags
-            .iter()
-            .any(|t| t.kind() == TagKind::t() && t.content().is_some_and(|c| c == "root"))
-        {
+        let root_patch = client.fetch_patch(self.patch_id.event_id).await?;
+        if !root_patch.is_root_patch() {
             return Err(N34Error::NotRootPatch);
         }
 
-        let root_author = root_patch.pubkey;
-        let root_patch = super::GitPatch::from_str(&root_patch.content)
-            .map_err(|err| N34Error::InvalidEvent(format!("Failed to parse the patch: {err}")))?;
-
-        tracing::info!("Found the root patch: `{}`", root_patch.subject);
-
-        let mut patches = client
-            .fetch_patch_series(self.patch_id.event_id, root_author)
-            .await?
-            .into_iter()
+        let events = client.fetch_ordered_patch_series(root_patch).await?;
+        let mut patches = events
+            .iter()
             .map(|p| {
-                let patch = super::GitPatch::from_str(&p.content).map_err(|err| {
-                    N34Error::InvalidPatch(format!(
-                        "Failed to parse the patch `{}`: {err}",
-                        p.id.to_bech32().expect("Infallible")
-                    ))
-                })?;
+                let patch = GitPatch::from_event(p)?;
                 N34Result::Ok((patch.filename(&output_path)?, patch))
             })
             .collect::<N34Result<Vec<_>>>()?;
-        patches.push((root_patch.filename(&output_path)?, root_patch));
-        patches.sort_unstable_by_key(|p| p.0.clone());
-        patches.dedup_by_key(|p| p.0.clone());
+        tracing::info!("Found the root patch: `{}`", patches[0].1.subject);
+
+        let mismatched = if self.no_verify {
+            Vec::new()
+        } else {
+            verify_commits(&events, &patches)
+        };
+
+        // The reply chain order is kept, so the patches can be piped to `git am`
+        let mut seen = HashSet::new();
+        patches.retain(|p| seen.insert(p.0.clone()));
 
         if !is_stdout && !is_current_dir && !output_path.exists() {
             fs::create_dir_all(&output_path)?;
@@ -155,6 +141,107 @@ impl CommandRunner for FetchArgs {
             }
         }
 
+        if !mismatched.is_empty() {
+            return Err(N34Error::CommitsMismatch(
+                mismatched
+                    .iter()
+                    .map(|subject| format!("`{subject}`"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
+
         Ok(())
     }
 }
+
+/// Recreates the commits of the patches that have a `commit` tag and returns
+/// the subjects of the mismatched ones. The patches are applied in a temporary
+/// worktree at their `parent-commit`, using the committer from the `committer`
+/// tag. Verification is skipped for the cover letter, for the patches without
+/// a `committer` tag and if the parent commit is not found locally.
+fn verify_commits(events: &[Event], patches: &[(PathBuf, GitPatch)]) -> Vec<String> {
+    let mut mismatched = Vec::new();
+    if git_utils::ensure_work_tree().is_err() {
+        tracing::info!("Not inside a git repository, skipping the commits verification");
+        return mismatched;
+    }
+
+    let mut worktree: Option<(git_utils::TempWorktree, String)> = None;
+    for (event, (_, patch)) in events.iter().zip(patches) {
+        if patch.is_cover_letter() {
+            continue;
+        }
+        let Some(expected) = event.patch_commit() else {
+            continue;
+        };
+        // The commit sha depends on the committer, it can't be recreated
+        // without it
+        let Some(committer) = event.patch_committer() else {
+            tracing::warn!(
+                "The patch `{}` has no `committer` tag, can't verify it",
+                patch.subject
+            );
+            continue;
+        };
+        let Some(parent) = event.patch_parent_commit() else {
+            tracing::warn!("The patch `{}` has no `parent-commit` tag", patch.subject);
+            continue;
+        };
+
+        // Reuse the worktree if the previous patch is the parent
+        if worktree.as_ref().is_none_or(|(_, head)| head != parent) {
+            if git_utils::commit_object(parent).is_none() {
+                tracing::warn!(
+                    "The parent commit `{parent}` of `{}` is not found locally, can't verify it",
+                    patch.subject
+                );
+                worktree = None;
+                continue;
+            }
+            match git_utils::TempWorktree::new(parent) {
+                Ok(tree) => worktree = Some((tree, parent.to_owned())),
+                Err(err) => {
+                    tracing::warn!("Can't create a worktree to verify the commits: {err}");
+                    return mismatched;
+                }
+            }
+        }
+
+        let (tree, head) = worktree.as_mut().expect("It's created above");
+        let result =
+            git_utils::am_in(tree.path(), &patch.inner, Some(&committer)).and_then(|commit| {
+                match event.patch_pgp_sig() {
+                    Some(sig) => git_utils::sign_head_in(tree.path(), sig),
+                    None => Ok(commit),
+                }
+            });
+
+        match result {
+            Ok(commit) if commit == expected => {
+                tracing::info!(
+                    "The patch `{}` recreates the commit `{commit}`",
+                    patch.subject
+                );
+                *head = commit;
+            }
+            Ok(commit) => {
+                tracing::error!(
+                    "The patch `{}` recreates the commit `{commit}` instead of `{expected}`",
+                    patch.subject
+                );
+                mismatched.push(patch.subject.clone());
+                *head = commit;
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Can't apply the patch `{}` to verify it: {err}",
+                    patch.subject
+                );
+                worktree = None;
+            }
+        }
+    }
+
+    mismatched
+}
@@ -0,0 +1,161 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use std::{fs, io, str::FromStr};
+
+use clap::Args;
+
+use super::GitPatch;
+use crate::{
+    cli::{
+        CliOptions,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt},
+        types::{NaddrOrSet, NostrEvent},
+    },
+    error::{N34Error, N34Result},
+    mbox::{self, Message},
+    nostr_utils::utils,
+};
+
+/// The headers kept from the mailing-list messages, the rest are added by the
+/// mailing list or the mail clients.
+const KEPT_HEADERS: [&str; 6] = [
+    "From",
+    "Date",
+    "Subject",
+    "Message-ID",
+    "In-Reply-To",
+    "References",
+];
+
+#[derive(Args, Debug)]
+pub struct ImportMboxArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs:         Option<Vec<NaddrOrSet>>,
+    /// Original patch ID if this is a revision of it. If omitted and the
+    /// series is a reroll, it's taken from the local records of the sent
+    /// series.
+    #[arg(long, value_name = "EVENT-ID")]
+    original_patch: Option<NostrEvent>,
+    /// The mbox of the mailing-list thread. Use `-` to read from stdin.
+    #[arg(value_name = "MBOX-PATH")]
+    mbox:           String,
+}
+
+impl CommandRunner for ImportMboxArgs {
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        let naddrs = utils::check_empty_naddrs(utils::naddrs_or_file(
+            self.naddrs.flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?)?;
+
+        let mbox = if self.mbox == "-" {
+            io::read_to_string(io::stdin())?
+        } else {
+            fs::read_to_string(&self.mbox)?
+        };
+        let patches = series_from_mbox(&mbox)?;
+        tracing::info!("Importing {} patches from the mbox", patches.len());
+
+        super::send::send_patch_series(
+            &options,
+            naddrs,
+            patches,
+            self.original_patch,
+            None,
+            Vec::new(),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Extracts the patch series from a mailing-list thread. The replies are
+/// skipped, and only the latest version of the series is kept, ordered by the
+/// patch numbers. The messages are converted to plain `git format-patch`
+/// messages.
+pub fn series_from_mbox(mbox: &str) -> N34Result<Vec<GitPatch>> {
+    let mut patches = mbox::split_mbox(mbox)
+        .iter()
+        .filter_map(|message| {
+            GitPatch::from_str(message)
+                .inspect_err(|err| tracing::debug!("Skipping a message: {err}"))
+                .ok()
+        })
+        .filter_map(|patch| {
+            // Replies to the patches start with `Re:`
+            if !patch.subject.starts_with('[') {
+                return None;
+            }
+            let (number, total) = super::patch_number_and_total(&patch.subject)?;
+            Some((super::patch_version(&patch.subject), number, total, patch))
+        })
+        .collect::<Vec<_>>();
+
+    let latest_version = patches
+        .iter()
+        .map(|(version, ..)| *version)
+        .max()
+        .ok_or_else(|| N34Error::InvalidPatch("No patches found in the mbox".to_owned()))?;
+    patches.retain(|(version, ..)| *version == latest_version);
+    patches.sort_by_key(|(_, number, ..)| *number);
+    patches.dedup_by_key(|(_, number, ..)| *number);
+
+    let total = patches[0].2;
+    let found = patches
+        .iter()
+        .filter(|(_, number, ..)| *number != 0)
+        .count();
+    if found != total as usize {
+        return Err(N34Error::InvalidPatch(format!(
+            "The mbox has {found} of the {total} patches of the series"
+        )));
+    }
+
+    patches
+        .into_iter()
+        .map(|(.., patch)| plain_patch(&patch))
+        .collect()
+}
+
+/// Converts the mailing-list message to a plain `git format-patch` message,
+/// with a decoded body and without the mailing list headers.
+fn plain_patch(patch: &GitPatch) -> N34Result<GitPatch> {
+    let message = Message::parse(&patch.inner).map_err(N34Error::InvalidPatch)?;
+    let headers = KEPT_HEADERS
+        .iter()
+        .filter_map(|name| {
+            message
+                .raw_header(name)
+                .map(|value| ((*name).to_owned(), value.to_owned()))
+        })
+        .collect();
+
+    GitPatch::from_str(
+        &Message::new_plain_text(patch.commit.clone(), headers, message.body).format(false),
+    )
+    .map_err(N34Error::InvalidPatch)
+}
@@ -0,0 +1,136 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use clap::Args;
+use nostr::event::{Event, EventId};
+
+use super::GitPatch;
+use crate::{
+    cli::{
+        CliOptions,
+        common_commands,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{NaddrOrSet, NostrEvent},
+    },
+    error::N34Result,
+    git_utils::{self, TempWorktree},
+    nostr_utils::{
+        NostrClient,
+        traits::{GitPatchUtils, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct InterdiffArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs: Option<Vec<NaddrOrSet>>,
+    /// The commit to apply a series on when its base commit is unknown or not
+    /// found locally.
+    #[arg(long, value_name = "REV", default_value = "HEAD")]
+    base:   String,
+    /// The old revision, the root patch or root revision ID
+    #[arg(value_name = "OLD-EVENT-ID")]
+    old:    NostrEvent,
+    /// The new revision, the root patch or root revision ID
+    #[arg(value_name = "NEW-EVENT-ID")]
+    new:    NostrEvent,
+}
+
+impl CommandRunner for InterdiffArgs {
+    const NEED_SIGNER: bool = false;
+
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        git_utils::ensure_work_tree()?;
+
+        let naddrs = utils::naddrs_or_file(
+            self.naddrs.flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client
+            .add_relays(&[naddrs.extract_relays(), self.old.relays, self.new.relays].concat())
+            .await;
+        let repos = client.fetch_repos(&naddrs.into_coordinates()).await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let old_series = fetch_series(&client, &self.old.event_id).await?;
+        let new_series = fetch_series(&client, &self.new.event_id).await?;
+
+        let (_old_tree, old_range) = apply_series(&old_series, &self.base)?;
+        let (_new_tree, new_range) = apply_series(&new_series, &self.base)?;
+
+        println!("{}", git_utils::range_diff(&old_range, &new_range)?);
+
+        Ok(())
+    }
+}
+
+/// Fetches the ordered patch series of the given root patch or root revision
+async fn fetch_series(client: &NostrClient, patch_id: &EventId) -> N34Result<Vec<Event>> {
+    let patch = client.fetch_patch(*patch_id).await?;
+    common_commands::get_patch_root_revision(&patch)?;
+    client.fetch_ordered_patch_series(patch).await
+}
+
+/// Applies the series in a temporary worktree and returns it with the revision
+/// range of the applied commits. The series is applied on the `parent-commit`
+/// of its first patch after the cover letter, or on its `base-commit`, falling
+/// back to the given base.
+fn apply_series(series: &[Event], fallback_base: &str) -> N34Result<(TempWorktree, String)> {
+    let patches = series
+        .iter()
+        .map(GitPatch::from_event)
+        .collect::<N34Result<Vec<_>>>()?;
+
+    let base = series
+        .iter()
+        .zip(&patches)
+        .find(|(_, patch)| !patch.is_cover_letter())
+        .and_then(|(event, _)| event.patch_parent_commit().map(str::to_owned))
+        .or_else(|| patches.iter().find_map(|p| p.base_commit.clone()))
+        .filter(|base| git_utils::commit_object(base).is_some())
+        .unwrap_or_else(|| {
+            tracing::info!(
+                "The base commit of `{}` is not found locally, applying it on `{fallback_base}`",
+                patches[0].subject
+            );
+            fallback_base.to_owned()
+        });
+    let base = git_utils::rev_parse_commit(&base)?;
+
+    let worktree = TempWorktree::new(&base)?;
+    let mbox = patches
+        .iter()
+        .map(|p| p.inner.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let head = git_utils::am_in(worktree.path(), &mbox, None)?;
+
+    Ok((worktree, format!("{base}..{head}")))
+}
@@ -21,7 +21,7 @@ use clap::Args;
 use crate::{
     cli::{
         CliOptions,
-        common_commands,
+        common_commands::{self, ListFilters},
         traits::CommandRunner,
         types::{EntityType, NaddrOrSet},
     },
@@ -37,10 +37,12 @@ pub struct ListArgs {
     ///
     /// If omitted, looks for a `nostr-address` file.
     #[arg(value_name = "NADDR-NIP05-OR-SET", value_delimiter = ',')]
-    naddrs: Option<Vec<NaddrOrSet>>,
-    /// Maximum number of patches to list
+    naddrs:  Option<Vec<NaddrOrSet>>,
+    /// Maximum number of patches in a page
     #[arg(long, default_value = "15")]
-    limit:  NonZeroUsize,
+    limit:   NonZeroUsize,
+    #[command(flatten)]
+    filters: ListFilters,
 }
 
 impl CommandRunner for ListArgs {
@@ -51,6 +53,8 @@ impl CommandRunner for ListArgs {
             options,
             self.naddrs,
             self.limit.into(),
+            self.filters,
+            None,
         )
         .await
     }
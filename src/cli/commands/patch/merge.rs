@@ -17,15 +17,28 @@
 use clap::Args;
 use nostr::hashes::sha1::Hash as Sha1Hash;
 
+use super::GitPatch;
 use crate::{
     cli::{
         CliOptions,
-        traits::{CommandRunner, VecNostrEventExt},
+        common_commands,
+        dependencies,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt, VecNostrEventExt},
         types::{EntityType, NaddrOrSet, NostrEvent, PatchPrStatus},
+        verdicts,
     },
     error::{N34Error, N34Result},
+    git_utils,
+    nostr_utils::{
+        NostrClient,
+        traits::{NaddrsUtils, ReposUtils},
+        utils,
+    },
 };
 
+/// Number of the latest commits to look for the merged patches in.
+const MERGED_PATCHES_DEPTH: usize = 1000;
+
 #[derive(Debug, Args)]
 pub struct MergeArgs {
     /// Repository addresses
@@ -47,19 +60,79 @@ pub struct MergeArgs {
     /// merged, not all.
     #[arg(long = "patches", value_name = "PATCH-EVENT-ID")]
     merged_patches: Vec<NostrEvent>,
-    /// The merge commit id
-    merge_commit:   Sha1Hash,
+    /// The merge commit id. Defaults to the last patch if it's on the
+    /// first-parent chain of `HEAD`, or the first merge on `HEAD` that merged
+    /// it.
+    merge_commit:   Option<Sha1Hash>,
+    /// Merge even if the merge policy is not satisfied. The override is
+    /// recorded in the status content
+    #[arg(long)]
+    force:          bool,
 }
 
 impl CommandRunner for MergeArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
-        crate::cli::common_commands::patch_pr_status_command::<{ EntityType::Patch as u8 }>(
-            options,
+        git_utils::ensure_work_tree()?;
+
+        let naddrs = utils::naddrs_or_file(
+            self.naddrs.clone().flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client
+            .add_relays(&[naddrs.extract_relays(), self.patch_id.relays.clone()].concat())
+            .await;
+        let repos = client
+            .fetch_repos(&naddrs.clone().into_coordinates())
+            .await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let patch = client.fetch_patch(self.patch_id.event_id).await?;
+        let merged_patches = self.merged_patches.into_event_ids();
+        let patches = if merged_patches.is_empty() {
+            client.fetch_ordered_patch_series(patch.clone()).await?
+        } else {
+            let mut patches = Vec::with_capacity(merged_patches.len());
+            for patch_id in &merged_patches {
+                patches.push(client.fetch_patch(*patch_id).await?);
+            }
+            patches
+        }
+        .iter()
+        .map(GitPatch::from_event)
+        .collect::<N34Result<Vec<_>>>()?;
+
+        let history = self
+            .merge_commit
+            .map(|commit| commit.to_string())
+            .unwrap_or_else(|| "HEAD".to_owned());
+        let tip = merged_patches_tip(&patches, &history)?;
+        let merge_commit = common_commands::verify_merge_commit(self.merge_commit, &tip)?;
+        let note = match &options.config.merge_policy {
+            Some(policy) => {
+                let (root_id, _) = common_commands::get_patch_root_revision(&patch)?;
+                verdicts::check_merge_policy(
+                    policy,
+                    &verdicts::fetch_patch_verdicts(&client, root_id).await?,
+                    &[naddrs.extract_owners(), repos.extract_maintainers()].concat(),
+                    &patch.pubkey,
+                    self.force,
+                )?
+            }
+            None => None,
+        };
+
+        common_commands::patch_pr_status_command::<{ EntityType::Patch as u8 }>(
+            &options,
             self.patch_id,
-            self.naddrs,
+            self.naddrs.clone(),
             PatchPrStatus::MergedApplied,
-            Some(either::Either::Left(self.merge_commit)),
-            self.merged_patches.into_event_ids(),
+            Some(either::Either::Left(merge_commit)),
+            merged_patches,
+            None,
+            note,
             |patch_status| {
                 if patch_status.is_merged_or_applied() {
                     return Err(N34Error::InvalidStatus(
@@ -82,6 +155,34 @@ impl CommandRunner for MergeArgs {
                 Ok(())
             },
         )
-        .await
+        .await?;
+
+        dependencies::handle_dependants(&options, self.naddrs, &patch, &merge_commit).await
+    }
+}
+
+/// Finds the patches in the latest commits of `history` by their patch ID,
+/// and returns the commit of the last one. Fails if one of them is not found.
+pub fn merged_patches_tip(patches: &[GitPatch], history: &str) -> N34Result<String> {
+    let patches = patches.iter().filter(|p| p.has_diff()).collect::<Vec<_>>();
+    let mbox = patches
+        .iter()
+        .map(|p| p.inner.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let patch_ids = git_utils::patch_ids(format!("{mbox}\n"))?;
+    let history_ids = git_utils::branch_patch_ids(history, MERGED_PATCHES_DEPTH)?;
+
+    let mut tip = None;
+    for (patch, patch_id) in patches.iter().zip(&patch_ids) {
+        tip = Some(history_ids.get(patch_id).cloned().ok_or_else(|| {
+            N34Error::PatchNotInHistory(
+                patch.subject.clone(),
+                MERGED_PATCHES_DEPTH,
+                history.to_owned(),
+            )
+        })?);
     }
+
+    tip.ok_or_else(|| N34Error::InvalidPatch("The series doesn't contain any patch".to_owned()))
 }
@@ -16,22 +16,40 @@
 
 /// `patch apply` suubcommand
 mod apply;
+/// `patch checkout` subcommand
+mod checkout;
 /// `patch close` subcommand
 mod close;
 /// `patch draft` subcommand
 mod draft;
+/// `patch export` subcommand
+mod export;
 /// `patch fetch` subcommand
 mod fetch;
+/// `patch import-mbox` subcommand
+mod import_mbox;
+/// `patch interdiff` subcommand
+mod interdiff;
 /// `patch list` subcommand
 mod list;
 /// `patch merge` subcommand
 mod merge;
 /// `patch reopen` subcommand
 mod reopen;
+/// `patch review` subcommand
+mod review;
+/// `patch revisions` subcommand
+mod revisions;
 /// `patch send` subcommand
 mod send;
+/// `patch sync-status` subcommand
+mod sync_status;
 #[cfg(test)]
 mod tests;
+/// `patch to-pr` subcommand
+mod to_pr;
+/// `patch view` subcommand
+mod view;
 
 use std::{
     path::{Path, PathBuf},
@@ -40,36 +58,58 @@ use std::{
 };
 
 use clap::Subcommand;
+use nostr::{event::Event, nips::nip19::ToBech32};
 use regex::Regex;
 
 use self::apply::ApplyArgs;
+use self::checkout::CheckoutArgs;
 use self::close::CloseArgs;
 use self::draft::DraftArgs;
+use self::export::ExportArgs;
 use self::fetch::FetchArgs;
+use self::import_mbox::ImportMboxArgs;
+use self::interdiff::InterdiffArgs;
 use self::list::ListArgs;
 use self::merge::MergeArgs;
+pub use self::merge::merged_patches_tip;
 use self::reopen::ReopenArgs;
+use self::review::ReviewArgs;
+use self::revisions::RevisionsArgs;
 use self::send::SendArgs;
+pub use self::send::send_patch_series;
+use self::sync_status::SyncStatusArgs;
+use self::to_pr::ToPrArgs;
+use self::view::ViewArgs;
 use super::{CliOptions, CommandRunner};
-use crate::error::{N34Error, N34Result};
+use crate::{
+    error::{N34Error, N34Result},
+    mbox::{self, Diffstat},
+};
+
+/// Regular expression for extracting the base commit added by `git
+/// format-patch --base`.
+static BASE_COMMIT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^base-commit: ([a-f0-9]{40})$").unwrap());
 
-/// Regular expression for checking the first line in the patch.
-pub static FROM_RE: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"^From [a-f0-9]{40} \w+ \w+ \d{1,2} \d{2}:\d{2}:\d{2} \d{4}$").unwrap()
-});
+/// Regular expression for extracting the `[PATCH ...]` prefix of a subject.
+/// Other words can be around `PATCH`, e.g. `[RFC PATCH v2 1/3]` or `[PATCH
+/// net-next v2 1/3]`.
+static PATCH_PREFIX_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(?<prefix>(?:[^\]]*\s)?PATCH(?:\s[^\]]*)?)\]").unwrap());
 
-/// Regular expression for extracting the patch subject.
-static SUBJECT_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"(?m)^Subject: (.*(?:\n .*)*)").unwrap());
+/// Regular expression for extracting the patch version from the prefix
+static PATCH_VERSION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)v(?<version>\d+)(?:\s|$)").unwrap());
 
-/// Regular expression for extracting the patch body.
-static BODY_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"\n\n((?:.|\n)*?)(?:\n--[ -]|\z)").unwrap());
+/// Regular expression for extracting the patch number and the series length
+/// from the prefix
+static PATCH_NUMBER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)(?<number>\d+)/(?<total>\d+)(?:\s|$)").unwrap());
 
-/// Regular expiration for extracting the patch version and number
-static PATCH_VERSION_NUMBER_RE: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"\[PATCH\s+(?:v(?<version>\d+)\s*)?(?<number>\d+)/(?:\d+)").unwrap()
-});
+/// Placeholder left by `git-format-patch` in the generated cover letter.
+pub const COVER_LETTER_PLACEHOLDER: &str = "*** SUBJECT HERE ***";
+/// Placeholder left by `git-format-patch` for the cover letter blurb.
+pub const COVER_LETTER_BLURB_PLACEHOLDER: &str = "*** BLURB HERE ***";
 
 /// Content of the hashtag representing the root patch.
 pub const ROOT_HASHTAG_CONTENT: &str = "root";
@@ -86,6 +126,20 @@ pub enum PatchSubcommands {
     Send(SendArgs),
     /// Fetch a patch
     Fetch(FetchArgs),
+    /// Export a patch series as a threaded mbox
+    Export(ExportArgs),
+    /// Send a mailing-list thread as a patch series
+    ImportMbox(ImportMboxArgs),
+    /// Apply a patch series to a new branch using `git am`
+    Checkout(CheckoutArgs),
+    /// View a patch series, its revisions and discussion
+    View(ViewArgs),
+    /// Review a patch with inline comments
+    Review(ReviewArgs),
+    /// List the revisions of a patch
+    Revisions(RevisionsArgs),
+    /// Show the range-diff between two revisions of a patch
+    Interdiff(InterdiffArgs),
     /// Close a patch
     Close(CloseArgs),
     /// Convert to draft
@@ -98,29 +152,77 @@ pub enum PatchSubcommands {
     Merge(MergeArgs),
     /// List patches
     List(ListArgs),
+    /// Mark the open patches found in the local branch as applied
+    SyncStatus(SyncStatusArgs),
+    /// Convert a patch series to a pull request
+    ToPr(ToPrArgs),
 }
 
 /// Represents a git patch
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct GitPatch {
     /// Full content of the patch file
-    pub inner:   String,
+    pub inner:       String,
     /// Short description of the patch changes
-    pub subject: String,
+    pub subject:     String,
     /// Detailed explanation of the patch changes
-    pub body:    String,
+    pub body:        String,
+    /// The commit of the patch, from the `From <sha>` line. `None` for the
+    /// cover letter, which carries the sha of the series tip.
+    pub commit:      Option<String>,
+    /// The patch author, from the `From:` header
+    pub author:      Option<String>,
+    /// The patch date, from the `Date:` header
+    pub date:        Option<String>,
+    /// The commit the series is based on, added by `git format-patch --base`
+    pub base_commit: Option<String>,
+    /// The `Message-ID` header, without the angle brackets
+    pub message_id:  Option<String>,
+    /// The commit message trailers, e.g. `Signed-off-by`
+    pub trailers:    Vec<(String, String)>,
+    /// The diffstat of the patch
+    pub diffstat:    Option<Diffstat>,
 }
 
 impl GitPatch {
+    /// Parses the patch from the content of the given patch event
+    pub fn from_event(event: &Event) -> N34Result<Self> {
+        Self::from_str(&event.content).map_err(|err| {
+            N34Error::InvalidPatch(format!(
+                "Failed to parse the patch `{}`: {err}",
+                event.id.to_bech32().expect("Infallible")
+            ))
+        })
+    }
+
+    /// Returns whether the patch is the cover letter of its series
+    pub fn is_cover_letter(&self) -> bool {
+        is_cover_letter(&self.subject)
+    }
+
+    /// Returns whether the patch contains a diff. The patches without one,
+    /// like the cover letter, have no patch ID.
+    pub fn has_diff(&self) -> bool {
+        self.inner
+            .lines()
+            .any(|line| line.starts_with("diff --git "))
+    }
+
     /// Returns the patch file name from the subject
     pub fn filename(&self, parent: impl AsRef<Path>) -> N34Result<PathBuf> {
-        let (patch_version, patch_number) = if self.subject.contains("[PATCH]") {
-            (String::new(), "1")
-        } else {
-            patch_version_and_subject(&self.subject)?
-        };
+        let (patch_number, _) = patch_number_and_total(&self.subject).ok_or_else(|| {
+            N34Error::InvalidEvent(format!(
+                "Can not parse the patch subject `{}`",
+                self.subject
+            ))
+        })?;
+        let patch_version =
+            match patch_prefix(&self.subject).and_then(|p| PATCH_VERSION_RE.captures(p)) {
+                Some(captures) => format!("v{}-", &captures["version"]),
+                None => String::new(),
+            };
 
-        let patch_name = if patch_number == "0" {
+        let patch_name = if patch_number == 0 {
             "cover-letter".to_owned()
         } else {
             patch_file_name(&self.subject)?
@@ -135,68 +237,118 @@ impl GitPatch {
 
 impl CommandRunner for PatchSubcommands {
     async fn run(self, options: CliOptions) -> N34Result<()> {
-        crate::run_command!(self, options, & Send Fetch Close Reopen Draft Apply Merge List)
+        crate::run_command!(self, options, & Send Fetch Export ImportMbox Checkout View Review Revisions Interdiff Close Reopen Draft Apply Merge List SyncStatus ToPr)
     }
 }
 
 impl FromStr for GitPatch {
     type Err = String;
 
+    /// Parses a single RFC 5322 message, from `git format-patch`, `git
+    /// send-email` or a public-inbox mailbox.
     fn from_str(patch_content: &str) -> Result<Self, Self::Err> {
-        if !patch_content
-            .split("\n")
-            .next()
-            .is_some_and(|line| FROM_RE.is_match(line))
-        {
-            return Err("The first line must start with 'From '.".to_owned());
-        }
-
-        // Regex for subject (handles multi-line subjects)
-        let subject = SUBJECT_RE
-            .captures(patch_content)
-            .and_then(|cap| cap.get(1))
-            .ok_or("No subject found")?
-            .as_str()
-            .trim()
-            .replace('\n', "")
-            .to_string();
+        let message = mbox::Message::parse(patch_content)?;
+        let (in_body_headers, content) = mbox::in_body_headers(&message.body);
+        // The in-body headers override the message headers, like `git am`
+        let header = |name: &str| {
+            in_body_headers
+                .iter()
+                .find(|(header, _)| header == name)
+                .map(|(_, value)| value.clone())
+                .or_else(|| message.header(name))
+                .map(|value| value.trim().to_owned())
+        };
 
-        // Regex for body
-        let body = BODY_RE
-            .captures(patch_content)
+        let subject = header("Subject").ok_or("No subject found")?;
+        // The patches that are not from a commit use the null sha, and the
+        // cover letter uses the sha of the series tip
+        let commit = message
+            .envelope
+            .clone()
+            .filter(|sha| sha.len() == 40 && sha.chars().all(|c| c.is_ascii_hexdigit()))
+            .filter(|sha| sha.chars().any(|c| c != '0'))
+            .filter(|_| !is_cover_letter(&subject));
+        let base_commit = BASE_COMMIT_RE
+            .captures(&message.body)
             .and_then(|cap| cap.get(1))
-            .ok_or("No body found")?
-            .as_str()
+            .map(|m| m.as_str().to_owned());
+
+        // The commit message ends at the diffstat separator, the diff or the
+        // signature
+        let body = content
+            .split('\n')
+            .take_while(|line| {
+                let line = line.trim_end_matches('\r');
+                line.trim_end() != "---"
+                    && line != "-- "
+                    && !line.starts_with("diff --git ")
+                    && !line.starts_with("Index: ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
             .trim()
-            .to_string();
+            .to_owned();
+
         Ok(Self {
             inner: patch_content.to_owned(),
+            trailers: mbox::parse_trailers(&body),
+            diffstat: mbox::Diffstat::parse(content),
+            message_id: message.message_id(),
+            author: header("From"),
+            date: header("Date"),
             subject,
             body,
+            commit,
+            base_commit,
         })
     }
 }
 
-/// Extracts the version prefix and patch number from a patch subject string.
-///
-/// The version prefix is formatted as "v{version}-" if present, or an empty
-/// string. The patch number is mandatory and will cause an error if not found.
-fn patch_version_and_subject(subject: &str) -> N34Result<(String, &str)> {
-    let captures = PATCH_VERSION_NUMBER_RE.captures(subject).ok_or_else(|| {
-        N34Error::InvalidEvent(format!("Can not parse the patch subject `{subject}`"))
-    })?;
-    Ok((
-        captures
-            .name("version")
-            .map(|m| format!("v{}-", m.as_str()))
-            .unwrap_or_default(),
-        captures
-            .name("number")
-            .map(|m| m.as_str())
-            .expect("It's not optional, regex will fail if it's not found"),
+/// Returns the content of the `[PATCH ...]` prefix of the subject, e.g.
+/// `RFC PATCH v2 1/3`.
+fn patch_prefix(subject: &str) -> Option<&str> {
+    PATCH_PREFIX_RE
+        .captures(subject)
+        .and_then(|captures| captures.name("prefix"))
+        .map(|prefix| prefix.as_str())
+}
+
+/// Returns the version of the patch from its subject, `1` if it has no version.
+fn patch_version(subject: &str) -> u32 {
+    patch_prefix(subject)
+        .and_then(|prefix| PATCH_VERSION_RE.captures(prefix))
+        .and_then(|captures| captures["version"].parse().ok())
+        .unwrap_or(1)
+}
+
+/// Returns the number of the patch in its series and the series length from
+/// its subject, e.g. `(2, 3)` for `[PATCH 2/3]`. A single patch, without a
+/// number, is `(1, 1)`.
+fn patch_number_and_total(subject: &str) -> Option<(u32, u32)> {
+    let prefix = patch_prefix(subject)?;
+    if !prefix.contains('/') {
+        return Some((1, 1));
+    }
+    let captures = PATCH_NUMBER_RE.captures(prefix)?;
+    Some((
+        captures["number"].parse().ok()?,
+        captures["total"].parse().ok()?,
     ))
 }
 
+/// Returns whether the subject is of a cover letter, the patch number `0`.
+fn is_cover_letter(subject: &str) -> bool {
+    patch_number_and_total(subject).is_some_and(|(number, _)| number == 0)
+}
+
+/// Returns the patch subject without the `[PATCH ...]` prefix.
+fn subject_without_prefix(subject: &str) -> &str {
+    subject
+        .split_once(']')
+        .map_or(subject, |(_, subject)| subject)
+        .trim()
+}
+
 /// Extracts a clean file name from the patch subject by removing version info
 /// and special characters. Converts to lowercase and ensures the name only
 /// contains alphanumeric, '.', '-', or '_' characters.
@@ -222,3 +374,14 @@ fn patch_file_name(subject: &str) -> N34Result<String> {
         .trim()
         .replace("--", "-"))
 }
+
+/// Formats the patch event subject and its ID
+fn format_patch_event(patch: &Event) -> String {
+    format!(
+        "{} ({})",
+        GitPatch::from_event(patch)
+            .map(|p| p.subject)
+            .unwrap_or_else(|_| "N/A".to_owned()),
+        patch.id.to_bech32().expect("Infallible")
+    )
+}
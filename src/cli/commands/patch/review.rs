@@ -0,0 +1,542 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use std::sync::LazyLock;
+
+use clap::{ArgGroup, Args};
+use futures::future;
+use nostr::{
+    event::{Event, EventBuilder, Tag, TagKind},
+    nips::nip22,
+};
+use regex::Regex;
+
+use super::GitPatch;
+use crate::{
+    cli::{
+        CliOptions,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{NaddrOrSet, NostrEvent},
+        verdicts::ReviewVerdict,
+    },
+    error::{N34Error, N34Result},
+    nostr_utils::{
+        NostrClient,
+        traits::{GitPatchUtils, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+/// Regular expression for parsing a hunk header, e.g. `@@ -10,7 +10,8 @@`.
+static HUNK_HEADER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^@@ -(?<old_start>\d+)(?:,(?<old_len>\d+))? \+(?<new_start>\d+)(?:,(?<new_len>\d+))? @@",
+    )
+    .unwrap()
+});
+
+/// The tag of the file that a review comment refers to.
+pub const FILE_TAG: &str = "file";
+/// The tag of the line range that a review comment refers to, in the new file.
+pub const LINES_TAG: &str = "lines";
+
+#[derive(Args, Debug)]
+#[clap(group(
+    ArgGroup::new("verdict").args(["approve", "request_changes", "comment"])
+))]
+pub struct ReviewArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs:          Option<Vec<NaddrOrSet>>,
+    /// Approve the patch series
+    #[arg(long)]
+    approve:         bool,
+    /// Request changes to the patch series
+    #[arg(long)]
+    request_changes: bool,
+    /// Review the patch series without a verdict
+    #[arg(long)]
+    comment:         bool,
+    /// The general review message, publishes it without opening the editor.
+    /// Requires a verdict
+    #[arg(short, long, requires = "verdict")]
+    message:         Option<String>,
+    /// The patch to review
+    #[arg(value_name = "EVENT-ID")]
+    patch_id:        NostrEvent,
+}
+
+/// A review comment parsed from the reviewed patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewComment {
+    /// The file that the comment refers to. `None` for a general comment
+    pub file:    Option<String>,
+    /// The first and last lines that the comment refers to, in the new file
+    pub lines:   Option<(u32, u32)>,
+    /// The comment, with the quoted hunk lines
+    pub content: String,
+}
+
+/// A review comment to render after the diff line it refers to.
+#[derive(Debug, Clone)]
+pub struct InlineReview {
+    /// The file that the comment refers to
+    pub file: String,
+    /// The last line that the comment refers to, in the new file
+    pub line: u32,
+    /// The rendered comment
+    pub text: String,
+}
+
+/// The position of a line in a unified diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine {
+    /// A `diff --git` line, starts a new file
+    File,
+    /// A hunk header, starts a new hunk
+    HunkHeader,
+    /// A hunk line, with its line number in the new file
+    Hunk(u32),
+    /// A line outside the hunks
+    Other,
+}
+
+/// Tracks the current file and line while walking a unified diff line by line.
+#[derive(Debug, Default)]
+pub struct DiffCursor {
+    /// The current file, by its new path
+    pub file:      Option<String>,
+    /// The remaining lines of the hunk in the old file
+    old_remaining: u32,
+    /// The remaining lines of the hunk in the new file
+    new_remaining: u32,
+    /// The next line number in the new file
+    new_line:      u32,
+}
+
+/// The review comments of a single hunk.
+struct HunkReview {
+    /// The file of the hunk
+    file:      Option<String>,
+    /// The first line of the hunk in the new file
+    start:     u32,
+    /// The last quoted line of the hunk
+    last_line: u32,
+    /// The last line that has a comment after it
+    end:       Option<u32>,
+    /// The hunk lines as written in the editor
+    text:      Vec<String>,
+}
+
+impl CommandRunner for ReviewArgs {
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        let naddrs = utils::check_empty_naddrs(utils::naddrs_or_file(
+            self.naddrs.flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?)?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+        let user_pubk = client.pubkey().await?;
+
+        client
+            .add_relays(&[naddrs.extract_relays(), self.patch_id.relays].concat())
+            .await;
+        let relays_list = client.user_relays_list(user_pubk).await?;
+        client
+            .add_relays(&utils::add_read_relays(relays_list.as_ref()))
+            .await;
+        let repos = client.fetch_repos(&naddrs.into_coordinates()).await?;
+        client.add_relays(&repos.extract_relays()).await;
+        let relay_hint = repos.extract_relays().first().cloned();
+
+        let patch = client.fetch_patch(self.patch_id.event_id).await?;
+        let root = match patch.patch_root() {
+            Some(root_id) => client.fetch_patch(root_id).await?,
+            None => patch.clone(),
+        };
+        let git_patch = GitPatch::from_event(&patch)?;
+
+        let verdict = ReviewVerdict::from_flags(self.approve, self.request_changes, self.comment);
+
+        let mut comments = if self.message.is_some() {
+            Vec::new()
+        } else {
+            parse_review(&utils::read_editor(
+                Some(&quote_patch(&git_patch.inner)),
+                ".patch",
+            )?)
+        };
+        ensure_general_comment(&mut comments, verdict, self.message);
+        if comments.is_empty() {
+            return Err(N34Error::EmptyReview);
+        }
+
+        let events = comments
+            .into_iter()
+            .enumerate()
+            .map(|(idx, comment)| {
+                // The verdict is carried by the first comment, the general one if any
+                let verdict = verdict.filter(|_| idx == 0);
+                build_review_comment(comment, &patch, &root, relay_hint.clone(), verdict)
+            })
+            .map(|builder| {
+                builder
+                    .dedup_tags()
+                    .pow(options.pow.unwrap_or_default())
+                    .build(user_pubk)
+            })
+            .collect::<Vec<_>>();
+
+        let write_relays = [
+            relays,
+            utils::add_write_relays(relays_list.as_ref()),
+            repos.extract_relays(),
+            client
+                .read_relays_from_users(&repos.extract_maintainers())
+                .await,
+            client.read_relays_from_user(patch.pubkey).await,
+        ]
+        .concat();
+
+        let nevents = future::join_all(events.into_iter().map(|mut event| {
+            async {
+                let event_id = event.id();
+                client
+                    .send_event_to(event, relays_list.as_ref(), &write_relays)
+                    .await
+                    .map(|r| utils::new_nevent(event_id, &r))?
+            }
+        }))
+        .await
+        .into_iter()
+        .collect::<N34Result<Vec<_>>>()?;
+
+        for nevent in nevents {
+            println!("Review comment created: {nevent}");
+        }
+
+        Ok(())
+    }
+}
+
+impl DiffCursor {
+    /// Walks to the given diff line and returns its position
+    pub fn next(&mut self, line: &str) -> DiffLine {
+        if self.old_remaining == 0 && self.new_remaining == 0 {
+            if let Some(paths) = line.strip_prefix("diff --git ") {
+                self.file = paths.rsplit_once(" b/").map(|(_, path)| path.to_owned());
+                return DiffLine::File;
+            }
+
+            let Some(captures) = HUNK_HEADER_RE.captures(line) else {
+                return DiffLine::Other;
+            };
+            let number = |name: &str| {
+                captures
+                    .name(name)
+                    .map_or(Some(1), |m| m.as_str().parse().ok())
+                    .unwrap_or_default()
+            };
+            self.old_remaining = number("old_len");
+            self.new_remaining = number("new_len");
+            self.new_line = number("new_start");
+            return DiffLine::HunkHeader;
+        }
+
+        match line.chars().next() {
+            Some('-') => {
+                self.old_remaining = self.old_remaining.saturating_sub(1);
+                DiffLine::Hunk(self.new_line)
+            }
+            Some('+') => {
+                self.new_remaining = self.new_remaining.saturating_sub(1);
+                self.new_line += 1;
+                DiffLine::Hunk(self.new_line - 1)
+            }
+            // `\ No newline at end of file`
+            Some('\\') => DiffLine::Hunk(self.new_line.saturating_sub(1)),
+            _ => {
+                self.old_remaining = self.old_remaining.saturating_sub(1);
+                self.new_remaining = self.new_remaining.saturating_sub(1);
+                self.new_line += 1;
+                DiffLine::Hunk(self.new_line - 1)
+            }
+        }
+    }
+
+    /// Returns the next line number in the new file
+    pub fn line(&self) -> u32 {
+        self.new_line
+    }
+}
+
+impl HunkReview {
+    /// Returns the review comment of the hunk, `None` if there is no comment in
+    /// it.
+    fn into_comment(mut self) -> Option<ReviewComment> {
+        let end = self.end?;
+        // Remove the quoted lines after the last comment
+        while self
+            .text
+            .last()
+            .is_some_and(|line| line.starts_with('>') || line.trim().is_empty())
+        {
+            self.text.pop();
+        }
+
+        Some(ReviewComment {
+            file:    self.file,
+            lines:   Some((self.start.min(end), end)),
+            content: self.text.join("\n"),
+        })
+    }
+}
+
+/// Quotes every line of the patch, to write the review comments between them.
+pub fn quote_patch(patch: &str) -> String {
+    patch
+        .lines()
+        .map(|line| format!("> {line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parses the review written between the quoted patch lines. Returns a comment
+/// for each hunk that has comments, and a general comment for the comments
+/// outside the hunks.
+pub fn parse_review(review: &str) -> Vec<ReviewComment> {
+    let mut cursor = DiffCursor::default();
+    let mut comments = Vec::new();
+    let mut general = Vec::new();
+    let mut hunk: Option<HunkReview> = None;
+
+    for line in review.lines() {
+        let quoted = if line == ">" {
+            Some("")
+        } else {
+            line.strip_prefix("> ")
+        };
+
+        let Some(quoted) = quoted else {
+            match hunk.as_mut() {
+                Some(hunk) => {
+                    if !line.trim().is_empty() {
+                        hunk.end = Some(hunk.last_line);
+                    }
+                    hunk.text.push(line.to_owned());
+                }
+                None => general.push(line),
+            }
+            continue;
+        };
+
+        match cursor.next(quoted) {
+            DiffLine::Hunk(number) => {
+                if let Some(hunk) = hunk.as_mut() {
+                    hunk.last_line = number;
+                    hunk.text.push(line.to_owned());
+                }
+            }
+            position => {
+                comments.extend(hunk.take().and_then(HunkReview::into_comment));
+                if position == DiffLine::HunkHeader {
+                    hunk = Some(HunkReview {
+                        file:      cursor.file.clone(),
+                        start:     cursor.line(),
+                        last_line: cursor.line(),
+                        end:       None,
+                        text:      vec![line.to_owned()],
+                    });
+                }
+            }
+        }
+    }
+    comments.extend(hunk.and_then(HunkReview::into_comment));
+
+    let general = general.join("\n").trim().to_owned();
+    if !general.is_empty() {
+        comments.insert(
+            0,
+            ReviewComment {
+                file:    None,
+                lines:   None,
+                content: general,
+            },
+        );
+    }
+
+    comments
+}
+
+/// Returns the review comment of the given patch from a NIP-22 comment, with
+/// the quoted hunk lines removed. `None` if it's not a review comment of this
+/// patch.
+pub fn inline_review(comment: &Event, patch: &Event, text_header: &str) -> Option<InlineReview> {
+    if !matches!(
+        nip22::extract_parent(comment),
+        Some(nip22::CommentTarget::Event { id, .. }) if id == &patch.id
+    ) {
+        return None;
+    }
+
+    let file = comment.tags.find(TagKind::custom(FILE_TAG))?.content()?;
+    let line = comment
+        .tags
+        .find(TagKind::custom(LINES_TAG))?
+        .as_slice()
+        .get(2)?
+        .parse()
+        .ok()?;
+    let text = comment
+        .content
+        .lines()
+        .filter(|line| !line.starts_with('>'))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(InlineReview {
+        file: file.to_owned(),
+        line,
+        text: format!("{text_header}\n{}", text.trim()),
+    })
+}
+
+/// Renders the hunks of the patch that have review comments, with each comment
+/// after the diff line it refers to.
+pub fn render_reviews(patch: &str, reviews: &[InlineReview]) -> String {
+    let mut cursor = DiffCursor::default();
+    // The hunks with their file, and their lines with the line numbers
+    let mut hunks: Vec<(Option<String>, Vec<_>)> = Vec::new();
+
+    for line in patch.lines() {
+        match cursor.next(line) {
+            DiffLine::HunkHeader => hunks.push((cursor.file.clone(), vec![(line, None)])),
+            DiffLine::Hunk(number) => {
+                if let Some((_, lines)) = hunks.last_mut() {
+                    lines.push((line, Some(number)));
+                }
+            }
+            DiffLine::File | DiffLine::Other => {}
+        }
+    }
+
+    let render_review = |review: &InlineReview| {
+        review
+            .text
+            .lines()
+            .map(|line| format!("    | {line}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let mut output = Vec::new();
+    let mut last_file = None;
+    for (file, lines) in &hunks {
+        let numbers = lines.iter().filter_map(|(_, n)| *n).collect::<Vec<_>>();
+        let hunk_reviews = reviews
+            .iter()
+            .filter(|r| {
+                file.as_deref() == Some(r.file.as_str())
+                    && numbers.first().is_some_and(|first| r.line >= *first)
+                    && numbers.last().is_some_and(|last| r.line <= *last)
+            })
+            .collect::<Vec<_>>();
+        if hunk_reviews.is_empty() {
+            continue;
+        }
+
+        if last_file != Some(file) {
+            output.push(file.clone().unwrap_or_default());
+            last_file = Some(file);
+        }
+        for (idx, (line, number)) in lines.iter().enumerate() {
+            output.push((*line).to_owned());
+            // Render after the last diff line with this number
+            let is_last =
+                number.is_some() && lines.get(idx + 1).is_none_or(|(_, next)| next != number);
+            if is_last {
+                output.extend(
+                    hunk_reviews
+                        .iter()
+                        .filter(|r| Some(r.line) == *number)
+                        .map(|r| render_review(r)),
+                );
+            }
+        }
+    }
+
+    output.join("\n")
+}
+
+/// Adds a general comment to carry the review verdict, if there is a verdict
+/// and the review has no general comment. Its content is the given message, or
+/// the default content of the verdict.
+fn ensure_general_comment(
+    comments: &mut Vec<ReviewComment>,
+    verdict: Option<ReviewVerdict>,
+    message: Option<String>,
+) {
+    let Some(verdict) = verdict else {
+        return;
+    };
+    if comments.iter().any(|c| c.file.is_none()) {
+        return;
+    }
+    if let Some(content) = message.or_else(|| verdict.default_content().map(str::to_owned)) {
+        comments.insert(
+            0,
+            ReviewComment {
+                file: None,
+                lines: None,
+                content,
+            },
+        );
+    }
+}
+
+/// Builds the NIP-22 comment of the review comment, replying to the reviewed
+/// patch, with the verdict label if any.
+fn build_review_comment(
+    comment: ReviewComment,
+    patch: &Event,
+    root: &Event,
+    relay_hint: Option<nostr::types::RelayUrl>,
+    verdict: Option<ReviewVerdict>,
+) -> EventBuilder {
+    let mut builder = EventBuilder::comment(comment.content, patch, Some(root), relay_hint);
+    if let Some(verdict) = verdict {
+        builder = builder.tags(verdict.tags());
+    }
+    if let Some(file) = comment.file {
+        builder = builder.tag(Tag::custom(TagKind::custom(FILE_TAG), [file]));
+    }
+    if let Some((start, end)) = comment.lines {
+        builder = builder.tag(Tag::custom(
+            TagKind::custom(LINES_TAG),
+            [start.to_string(), end.to_string()],
+        ));
+    }
+    builder
+}
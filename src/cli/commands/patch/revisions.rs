@@ -0,0 +1,123 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use clap::Args;
+
+use crate::{
+    cli::{
+        CliOptions,
+        common_commands,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{NaddrOrSet, NostrEvent, PatchPrStatus},
+    },
+    error::N34Result,
+    nostr_utils::{
+        NostrClient,
+        traits::{NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct RevisionsArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs:   Option<Vec<NaddrOrSet>>,
+    /// The root patch or any of its root revisions ID
+    #[arg(value_name = "EVENT-ID")]
+    patch_id: NostrEvent,
+}
+
+impl CommandRunner for RevisionsArgs {
+    const NEED_SIGNER: bool = false;
+
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        let naddrs = utils::naddrs_or_file(
+            self.naddrs.flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client.add_relays(&naddrs.extract_relays()).await;
+        client.add_relays(&self.patch_id.relays).await;
+        let repos = client.fetch_repos(&naddrs.into_coordinates()).await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let patch = client.fetch_patch(self.patch_id.event_id).await?;
+        let (root_id, _) = common_commands::get_patch_root_revision(&patch)?;
+        let root_patch = if root_id == patch.id {
+            patch
+        } else {
+            client.fetch_patch(root_id).await?
+        };
+        let revisions = client.fetch_patch_revisions(root_id).await?;
+        let authorized_pubkeys =
+            [repos.extract_maintainers().as_slice(), &[root_patch.pubkey]].concat();
+
+        let mut statuses = Vec::with_capacity(revisions.len() + 1);
+        for revision in &revisions {
+            statuses.push(
+                client
+                    .fetch_patch_status(root_id, Some(revision.id), authorized_pubkeys.clone())
+                    .await?,
+            );
+        }
+        // The original patch is closed if one of its revisions is the merged
+        // or applied one
+        let root_status = if statuses.iter().any(PatchPrStatus::is_merged_or_applied) {
+            PatchPrStatus::Closed
+        } else {
+            client
+                .fetch_patch_status(root_id, None, authorized_pubkeys)
+                .await?
+        };
+        statuses.insert(0, root_status);
+
+        let all_revisions = [vec![root_patch], revisions].concat();
+        let usernames = client
+            .get_usernames(all_revisions.iter().map(|e| e.pubkey))
+            .await;
+
+        let lines = all_revisions
+            .iter()
+            .zip(statuses)
+            .enumerate()
+            .map(|(idx, (revision, status))| {
+                format!(
+                    "v{}: ({status}) {}\nAt {} - [by {}]",
+                    idx + 1,
+                    super::format_patch_event(revision),
+                    utils::format_date(revision.created_at),
+                    usernames[&revision.pubkey]
+                )
+            })
+            .collect::<Vec<_>>();
+
+        println!("{}", lines.join("\n\n"));
+
+        Ok(())
+    }
+}
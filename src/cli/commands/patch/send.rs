@@ -22,7 +22,7 @@ use nostr::{
     event::{EventBuilder, EventId, Kind, Tag, TagKind, Tags, UnsignedEvent},
     hashes::sha1::Hash as Sha1Hash,
     key::PublicKey,
-    nips::{nip01::Coordinate, nip10::Marker},
+    nips::{nip01::Coordinate, nip10::Marker, nip19::Nip19Coordinate},
     types::RelayUrl,
 };
 
@@ -30,11 +30,20 @@ use super::GitPatch;
 use crate::{
     cli::{
         CliOptions,
-        patch::{REVISION_ROOT_HASHTAG_CONTENT, ROOT_HASHTAG_CONTENT},
-        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt, VecPatchesExt},
+        patch::{COVER_LETTER_PLACEHOLDER, REVISION_ROOT_HASHTAG_CONTENT, ROOT_HASHTAG_CONTENT},
+        series_records::{SeriesRecord, SeriesRecords},
+        traits::{
+            CommandRunner,
+            OptionNaddrOrSetVecExt,
+            RelayOrSetVecExt,
+            VecNostrEventExt,
+            VecPatchesExt,
+        },
         types::{NaddrOrSet, NostrEvent},
+        utils as cli_utils,
     },
-    error::N34Result,
+    error::{N34Error, N34Result},
+    git_utils,
     nostr_utils::{
         NostrClient,
         traits::{NaddrsUtils, ReposUtils},
@@ -63,11 +72,39 @@ pub struct SendArgs {
     ///
     /// For p-tagging users, include them in the cover letter using
     /// `nostr:npub1...`.
-    #[arg(value_name = "PATCH-PATH", required = true, value_parser = parse_patch_path)]
+    #[arg(
+        value_name = "PATCH-PATH",
+        required_unless_present = "range",
+        conflicts_with = "range",
+        value_parser = parse_patch_path
+    )]
     patches:        Vec<Option<GitPatch>>,
-    /// Original patch ID if this is a revision of it
+    /// Generate the patches from a git revision range using
+    /// `git-format-patch`, e.g. `origin/master..HEAD`.
+    #[arg(long, value_name = "REVISION-RANGE")]
+    range:          Option<String>,
+    /// Generate a cover letter and open it in the editor to write its subject
+    /// and blurb.
+    #[arg(long, requires = "range")]
+    cover_letter:   bool,
+    /// Mark the series as the N-th iteration of the patch, like `git
+    /// format-patch -v N`. It has no `-v` short flag, that's the global
+    /// `--verbose`.
+    #[arg(long, value_name = "N", requires = "range")]
+    reroll_count:   Option<u32>,
+    /// The base commit of the series, recorded as `base-commit` in the
+    /// patches.
+    #[arg(long, value_name = "COMMIT", requires = "range")]
+    base:           Option<String>,
+    /// Original patch ID if this is a revision of it. If omitted and the
+    /// patches are a reroll, e.g. `[PATCH v2 ...]`, it's taken from the local
+    /// records of the sent series.
     #[arg(long, value_name = "EVENT-ID")]
     original_patch: Option<NostrEvent>,
+    /// The patch or pull request this series is built on top of. Can be given
+    /// multiple times.
+    #[arg(long, value_name = "EVENT-ID")]
+    depends_on:     Vec<NostrEvent>,
 }
 
 impl CommandRunner for SendArgs {
@@ -77,73 +114,179 @@ impl CommandRunner for SendArgs {
             &utils::nostr_address_path()?,
         )?)?;
 
-        let patches = self.patches.process_patches().await?;
-        let repo_coordinates = naddrs.clone().into_coordinates();
-        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
-        let client = NostrClient::init(&options, &relays).await;
-        let user_pubk = client.pubkey().await?;
+        let patches = if let Some(range) = &self.range {
+            patches_from_range(
+                range,
+                self.cover_letter,
+                self.reroll_count,
+                self.base.as_deref(),
+            )?
+        } else {
+            self.patches.process_patches().await?
+        };
+        // The branch is only known when the patches are generated from the
+        // current repository
+        let branch = self
+            .range
+            .as_ref()
+            .and_then(|_| git_utils::current_branch());
 
-        client.add_relays(&naddrs.extract_relays()).await;
-        if let Some(original_patch) = &self.original_patch {
-            client.add_relays(&original_patch.relays).await;
-        }
-        let relays_list = client.user_relays_list(user_pubk).await?;
-        client
-            .add_relays(&utils::add_read_relays(relays_list.as_ref()))
-            .await;
-        let repos = client.fetch_repos(&repo_coordinates).await?;
-        let euc = repos.extract_euc();
-        let maintainers = repos.extract_maintainers();
-        client.add_relays(&repos.extract_relays()).await;
-
-        let (events, events_write_relays) = make_patch_series(
-            &client,
+        send_patch_series(
+            &options,
+            naddrs,
             patches,
-            self.original_patch.as_ref().map(|e| e.event_id),
-            repos.extract_relays().first().cloned(),
-            repo_coordinates,
-            euc,
-            user_pubk,
+            self.original_patch,
+            branch,
+            self.depends_on.into_depends_on_tags(),
         )
         .await?;
+        Ok(())
+    }
+}
+
+/// Sends the patch series to the repositories and records it in the local
+/// series records, returns the root patch ID. The original patch is taken from
+/// the records if the series is a reroll and it's not given. The `root_tags`
+/// are added to the root patch only.
+pub async fn send_patch_series(
+    options: &CliOptions,
+    naddrs: Vec<Nip19Coordinate>,
+    patches: Vec<GitPatch>,
+    original_patch_event: Option<NostrEvent>,
+    branch: Option<String>,
+    root_tags: Vec<Tag>,
+) -> N34Result<EventId> {
+    let repo_coordinates = naddrs.clone().into_coordinates();
+    let version = super::patch_version(&patches[0].subject);
+    let series_subject = super::subject_without_prefix(&patches[0].subject).to_owned();
+    let mut records = SeriesRecords::load().unwrap_or_else(|err| {
+        tracing::warn!("Can't load the series records, rerolls won't be linked: {err}");
+        SeriesRecords::default()
+    });
+    let original_patch = original_patch_event
+        .as_ref()
+        .map(|e| e.event_id)
+        .or_else(|| {
+            find_original_patch(
+                &records,
+                &repo_coordinates,
+                branch.as_deref(),
+                &series_subject,
+                version,
+            )
+        });
+    let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+    let client = NostrClient::init(options, &relays).await;
+    let user_pubk = client.pubkey().await?;
 
-        let write_relays = [
-            relays,
-            repos.extract_relays(),
-            events_write_relays,
-            naddrs.extract_relays(),
-            self.original_patch.map(|e| e.relays).unwrap_or_default(),
-            utils::add_write_relays(relays_list.as_ref()),
-            client.read_relays_from_users(&maintainers).await,
-        ]
-        .concat();
-
-        tracing::trace!(write_relays = ?write_relays, "Write relays of the patches");
-        let nevents = future::join_all(events.into_iter().map(|mut event| {
-            async {
-                let event_id = event.id();
-                let subject = event
-                    .tags
-                    .find(TagKind::Alt)
-                    .and_then(Tag::content)
-                    .expect("There is an alt")
-                    .replace(PATCH_ALT_PREFIX, "");
-                client
-                    .send_event_to(event, relays_list.as_ref(), &write_relays)
-                    .await
-                    .map(|r| Ok((subject, utils::new_nevent(event_id, &r)?)))?
-            }
-        }))
-        .await
-        .into_iter()
-        .collect::<N34Result<Vec<_>>>()?;
-
-        for (subject, nevent) in nevents {
-            println!("Created '{subject}': {nevent}");
+    client.add_relays(&naddrs.extract_relays()).await;
+    if let Some(original_patch) = &original_patch_event {
+        client.add_relays(&original_patch.relays).await;
+    }
+    let relays_list = client.user_relays_list(user_pubk).await?;
+    client
+        .add_relays(&utils::add_read_relays(relays_list.as_ref()))
+        .await;
+    let repos = client.fetch_repos(&repo_coordinates).await?;
+    let euc = repos.extract_euc();
+    let maintainers = repos.extract_maintainers();
+    client.add_relays(&repos.extract_relays()).await;
+
+    let (events, events_write_relays) = make_patch_series(
+        &client,
+        patches,
+        original_patch,
+        repos.extract_relays().first().cloned(),
+        repo_coordinates.clone(),
+        euc,
+        user_pubk,
+        root_tags,
+    )
+    .await?;
+
+    let write_relays = [
+        relays,
+        repos.extract_relays(),
+        events_write_relays,
+        naddrs.extract_relays(),
+        original_patch_event.map(|e| e.relays).unwrap_or_default(),
+        utils::add_write_relays(relays_list.as_ref()),
+        client.read_relays_from_users(&maintainers).await,
+    ]
+    .concat();
+
+    tracing::trace!(write_relays = ?write_relays, "Write relays of the patches");
+    let root_id = events[0].id.expect("There is an id");
+    let nevents = future::join_all(events.into_iter().map(|mut event| {
+        async {
+            let event_id = event.id();
+            let subject = event
+                .tags
+                .find(TagKind::Alt)
+                .and_then(Tag::content)
+                .expect("There is an alt")
+                .replace(PATCH_ALT_PREFIX, "");
+            client
+                .send_event_to(event, relays_list.as_ref(), &write_relays)
+                .await
+                .map(|r| Ok((subject, utils::new_nevent(event_id, &r)?)))?
         }
+    }))
+    .await
+    .into_iter()
+    .collect::<N34Result<Vec<_>>>()?;
 
-        Ok(())
+    for (subject, nevent) in nevents {
+        println!("Created '{subject}': {nevent}");
+    }
+
+    for coordinate in repo_coordinates {
+        records.record(SeriesRecord {
+            repo: coordinate.to_string(),
+            branch: branch.clone(),
+            subject: series_subject.clone(),
+            original: original_patch.unwrap_or(root_id),
+            latest: root_id,
+            version,
+        });
     }
+    // The patches are already sent, failing here will only confuse the user
+    if let Err(err) = records.dump() {
+        tracing::warn!("Failed to save the series record: {err}");
+    }
+
+    Ok(root_id)
+}
+
+/// Finds the original patch of a reroll in the local series records. Returns
+/// `None` if the series is the first version or if it's not recorded.
+fn find_original_patch(
+    records: &SeriesRecords,
+    repo_coordinates: &[Coordinate],
+    branch: Option<&str>,
+    subject: &str,
+    version: u32,
+) -> Option<EventId> {
+    if version < 2 {
+        return None;
+    }
+
+    let record = repo_coordinates
+        .iter()
+        .find_map(|c| records.find(&c.to_string(), branch, subject));
+    if let Some(record) = record {
+        tracing::info!(
+            "Sending v{version} as a revision of `{}` (v{})",
+            record.original,
+            record.version
+        );
+    } else {
+        tracing::warn!(
+            "No record of a previous version of `{subject}`, sending it as a new series. Use \
+             `--original-patch` to make it a revision"
+        );
+    }
+    record.map(|r| r.original)
 }
 
 /// If the patch path is '-', it indicates that patches will be retrieved from
@@ -162,6 +305,38 @@ fn parse_patch_path(patch_path: &str) -> Result<Option<GitPatch>, String> {
     GitPatch::from_str(&patch_content).map(Option::Some)
 }
 
+/// Generates the patches of the given revision range using `git-format-patch`.
+/// If a cover letter is requested, it is opened in the editor to fill its
+/// subject and blurb.
+fn patches_from_range(
+    range: &str,
+    cover_letter: bool,
+    reroll_count: Option<u32>,
+    base: Option<&str>,
+) -> N34Result<Vec<GitPatch>> {
+    git_utils::ensure_work_tree()?;
+    let mbox = git_utils::format_patch(range, cover_letter, reroll_count, base)?;
+    if mbox.is_empty() {
+        return Err(N34Error::InvalidPatch(format!(
+            "No commits in the range `{range}`"
+        )));
+    }
+    let mut patches = cli_utils::split_patches(mbox)?;
+
+    if cover_letter {
+        let cover_letter = utils::read_editor(Some(&patches[0].inner), ".patch")?;
+        if cover_letter.contains(COVER_LETTER_PLACEHOLDER) {
+            return Err(N34Error::InvalidPatch(
+                "The cover letter subject and blurb must be written".to_owned(),
+            ));
+        }
+        patches[0] = GitPatch::from_str(&cover_letter).map_err(N34Error::InvalidPatch)?;
+    }
+
+    Ok(patches)
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn make_patch_series(
     client: &NostrClient,
     patches: Vec<GitPatch>,
@@ -170,11 +345,21 @@ async fn make_patch_series(
     repo_coordinates: Vec<Coordinate>,
     euc: Option<&Sha1Hash>,
     author_pkey: PublicKey,
+    root_tags: Vec<Tag>,
 ) -> N34Result<(Vec<UnsignedEvent>, Vec<RelayUrl>)> {
     let mut write_relays = Vec::new();
     let mut patch_series = Vec::new();
     let mut patches = patches.into_iter();
     let root_patch = patches.next().expect("Patches can't be empty");
+    // The parent of a commit that is not found locally is the previous patch
+    // commit, or the series base commit for the first one. The cover letter
+    // has no commit, so the first patch after it gets the base commit.
+    let base_commit = root_patch.base_commit.clone();
+    let mut previous_commit = if root_patch.is_cover_letter() {
+        base_commit.clone()
+    } else {
+        root_patch.commit.clone().or(base_commit.clone())
+    };
     let (root_event, root_relays) = make_patch(
         client,
         root_patch,
@@ -183,7 +368,9 @@ async fn make_patch_series(
         relay_hint.as_ref(),
         &repo_coordinates,
         euc,
+        base_commit.as_deref(),
         author_pkey,
+        root_tags,
     )
     .await;
     write_relays.extend(root_relays);
@@ -192,6 +379,7 @@ async fn make_patch_series(
     patch_series.push(root_event);
 
     for patch in patches {
+        let commit = patch.commit.clone();
         let (patch_event, patch_relays) = make_patch(
             client,
             patch,
@@ -200,9 +388,12 @@ async fn make_patch_series(
             relay_hint.as_ref(),
             &repo_coordinates,
             euc,
+            previous_commit.as_deref(),
             author_pkey,
+            Vec::new(),
         )
         .await;
+        previous_commit = commit.or(previous_commit);
         previous_patch = patch_event.id.expect("there is an id");
         write_relays.extend(patch_relays);
         patch_series.push(patch_event);
@@ -220,7 +411,9 @@ async fn make_patch(
     write_relay: Option<&RelayUrl>,
     repo_coordinates: &[Coordinate],
     euc: Option<&Sha1Hash>,
+    parent_commit: Option<&str>,
     author_pkey: PublicKey,
+    extra_tags: Vec<Tag>,
 ) -> (UnsignedEvent, Vec<RelayUrl>) {
     let content_details = client.parse_content(&patch.body).await;
     let content_relays = content_details.write_relays.clone();
@@ -234,6 +427,7 @@ async fn make_patch(
     // The `EventBuilder::dedup_tags` function then removes the 'reply' marker as a
     // duplicate.
     let mut safe_dedup_tags = Tags::new();
+    safe_dedup_tags.extend(commit_tags(&patch, parent_commit));
     safe_dedup_tags.push(Tag::alt(format!("{PATCH_ALT_PREFIX}{}", patch.subject)));
     safe_dedup_tags.push(Tag::description(patch.subject));
     safe_dedup_tags.extend(content_details.into_tags());
@@ -251,7 +445,9 @@ async fn make_patch(
         safe_dedup_tags.push(Tag::reference(euc.to_string()));
     }
     safe_dedup_tags.dedup();
-    let mut event_builder = EventBuilder::new(Kind::GitPatch, patch.inner).tags(safe_dedup_tags);
+    let mut event_builder = EventBuilder::new(Kind::GitPatch, patch.inner)
+        .tags(safe_dedup_tags)
+        .tags(extra_tags);
 
     // If the root is None, this indicates we're handling the root event
     if let Some(root_id) = root {
@@ -283,3 +479,47 @@ async fn make_patch(
         content_relays.into_iter().collect(),
     )
 }
+
+/// Returns the NIP-34 commit tags of the patch. The `committer` and
+/// `commit-pgp-sig` tags are only added if the commit is found in the local
+/// repository, as they can't be known from the patch itself. The given parent
+/// is used when the commit is not found locally.
+fn commit_tags(patch: &GitPatch, parent_commit: Option<&str>) -> Vec<Tag> {
+    let Some(commit) = patch.commit.as_deref() else {
+        return Vec::new();
+    };
+    let local_commit = git_utils::commit_object(commit);
+
+    let mut tags = vec![
+        Tag::custom(TagKind::custom("commit"), [commit]),
+        Tag::reference(commit),
+    ];
+
+    if let Some(parent) = local_commit
+        .as_ref()
+        .and_then(|c| c.parents.first().cloned())
+        .or_else(|| parent_commit.map(str::to_owned))
+    {
+        tags.push(Tag::custom(TagKind::custom("parent-commit"), [parent]));
+    }
+
+    if let Some(local_commit) = local_commit
+        && let Some(committer) = local_commit.committer
+    {
+        tags.push(Tag::custom(
+            TagKind::custom("committer"),
+            [
+                committer.name,
+                committer.email,
+                committer.timestamp.to_string(),
+                committer.offset.to_string(),
+            ],
+        ));
+        tags.push(Tag::custom(
+            TagKind::custom("commit-pgp-sig"),
+            [local_commit.pgp_sig.unwrap_or_default()],
+        ));
+    }
+
+    tags
+}
@@ -0,0 +1,151 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use std::{num::NonZeroUsize, str::FromStr};
+
+use clap::Args;
+use nostr::hashes::sha1::Hash as Sha1Hash;
+
+use super::GitPatch;
+use crate::{
+    cli::{
+        CliOptions,
+        common_commands,
+        traits::{CommandRunner, RelayOrSetVecExt},
+        types::{EntityType, NaddrOrSet, NostrEvent, PatchPrStatus},
+    },
+    error::{N34Error, N34Result},
+    git_utils,
+    nostr_utils::NostrClient,
+};
+
+#[derive(Debug, Args)]
+pub struct SyncStatusArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs:  Option<Vec<NaddrOrSet>>,
+    /// The branch to look for the applied patches in. Defaults to the default
+    /// branch of the repository.
+    #[arg(short, long)]
+    branch:  Option<String>,
+    /// Maximum number of patches to check
+    #[arg(long, default_value = "15")]
+    limit:   NonZeroUsize,
+    /// Number of the latest branch commits to look for the patches in
+    #[arg(long, default_value = "1000")]
+    depth:   NonZeroUsize,
+    /// Only show the applied patches without publishing their status
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl CommandRunner for SyncStatusArgs {
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        git_utils::ensure_work_tree()?;
+        let branch = match self.branch {
+            Some(branch) => branch,
+            None => git_utils::default_branch()?,
+        };
+        tracing::info!("Looking for the applied patches in `{branch}`");
+        let branch_ids = git_utils::branch_patch_ids(&branch, self.depth.into())?;
+
+        let open_patches = common_commands::fetch_pr_patches_and_issues::<
+            { EntityType::Patch as u8 },
+        >(&options, self.naddrs.clone(), self.limit.into())
+        .await?
+        .into_iter()
+        .filter(|(_, (_, kind))| *kind == PatchPrStatus::Open.kind().as_u16())
+        .map(|(event, _)| event);
+
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        for root_patch in open_patches {
+            let patches = client
+                .fetch_ordered_patch_series(root_patch.clone())
+                .await?
+                .iter()
+                .map(GitPatch::from_event)
+                .collect::<N34Result<Vec<_>>>()?;
+            let subject = patches[0].subject.clone();
+            let mbox = patches
+                .into_iter()
+                .filter(GitPatch::has_diff)
+                .map(|p| p.inner)
+                .collect::<Vec<_>>();
+            if mbox.is_empty() {
+                continue;
+            }
+
+            let patch_ids = git_utils::patch_ids(format!("{}\n", mbox.join("\n")))?;
+            let applied_commits = patch_ids
+                .iter()
+                .filter_map(|id| branch_ids.get(id))
+                .collect::<Vec<_>>();
+
+            if applied_commits.is_empty() {
+                continue;
+            }
+            if applied_commits.len() != patch_ids.len() {
+                println!(
+                    "`{subject}` is partially applied ({}/{} patches), mark it with `patch apply`",
+                    applied_commits.len(),
+                    patch_ids.len()
+                );
+                continue;
+            }
+
+            println!("`{subject}` is applied in `{branch}`");
+            if self.dry_run {
+                continue;
+            }
+
+            let applied_commits = applied_commits
+                .into_iter()
+                .map(|c| {
+                    Sha1Hash::from_str(c)
+                        .map_err(|err| N34Error::GitCommand("patch-id".to_owned(), err.to_string()))
+                })
+                .collect::<N34Result<Vec<_>>>()?;
+            common_commands::patch_pr_status_command::<{ EntityType::Patch as u8 }>(
+                &options,
+                NostrEvent {
+                    event_id: root_patch.id,
+                    relays:   Vec::new(),
+                },
+                self.naddrs.clone(),
+                PatchPrStatus::MergedApplied,
+                Some(either::Either::Right(applied_commits)),
+                Vec::new(),
+                None,
+                None,
+                super::apply::check_can_apply,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -261,12 +261,253 @@ base-commit: f670859b92d525874fd621452080c8479964ac6a"
     );
 }
 
+#[test]
+fn patch_commit_metadata() {
+    let patch_content = r#"From 24e8522268ad675996fc3b35209ce23951236bdc Mon Sep 17 00:00:00 2001
+From: Awiteb <a@4rs.nl>
+Date: Tue, 27 May 2025 19:20:42 +0000
+Subject: [PATCH 1/1] chore: a to abc
+
+Abc patch
+---
+ src/main.rs | 2 +-
+
+base-commit: f670859b92d525874fd621452080c8479964ac6a
+-- 
+2.49.0"#;
+    let patch = GitPatch::from_str(patch_content).unwrap();
+    assert_eq!(
+        patch.commit.as_deref(),
+        Some("24e8522268ad675996fc3b35209ce23951236bdc")
+    );
+    assert_eq!(patch.author.as_deref(), Some("Awiteb <a@4rs.nl>"));
+    assert_eq!(
+        patch.date.as_deref(),
+        Some("Tue, 27 May 2025 19:20:42 +0000")
+    );
+    assert_eq!(
+        patch.base_commit.as_deref(),
+        Some("f670859b92d525874fd621452080c8479964ac6a")
+    );
+}
+
+#[test]
+fn patch_cover_letter_without_commit() {
+    // `git format-patch --cover-letter` uses the sha of the series tip
+    let patch_content = r#"From 864f3018f62ab2e1265edb670d5493dafe7d2cb2 Mon Sep 17 00:00:00 2001
+From: Awiteb <a@4rs.nl>
+Date: Tue, 3 Jun 2025 08:41:12 +0000
+Subject: [PATCH 0/2] feat: Some test just a test
+
+Cover body
+-- 
+2.49.0"#;
+    let patch = GitPatch::from_str(patch_content).unwrap();
+    assert!(patch.is_cover_letter());
+    assert!(!patch.has_diff());
+    assert_eq!(patch.commit, None);
+    assert_eq!(patch.base_commit, None);
+}
+
+#[test]
+fn patch_encoded_subject_without_body() {
+    let patch_content = r#"From 24e8522268ad675996fc3b35209ce23951236bdc Mon Sep 17 00:00:00 2001
+From: =?UTF-8?q?Ren=C3=A9?= <rene@example.com>
+Date: Tue, 27 May 2025 19:20:42 +0000
+Subject: [PATCH] =?UTF-8?q?docs:=20caf=C3=A9?=
+ =?UTF-8?q?=20menu?=
+MIME-Version: 1.0
+Content-Type: text/plain; charset=UTF-8
+Content-Transfer-Encoding: 8bit
+
+---
+ README.md | 2 +-
+ 1 file changed, 1 insertion(+), 1 deletion(-)
+
+diff --git a/README.md b/README.md
+-- 
+2.49.0"#;
+    let patch = GitPatch::from_str(patch_content).unwrap();
+    assert_eq!(patch.subject, "[PATCH] docs: café menu");
+    assert_eq!(patch.author.as_deref(), Some("René <rene@example.com>"));
+    assert_eq!(patch.body, "");
+    assert_eq!(
+        patch.diffstat,
+        Some(Diffstat {
+            files:      vec!["README.md".to_owned()],
+            changed:    1,
+            insertions: 1,
+            deletions:  1,
+        })
+    );
+}
+
+#[test]
+fn patch_send_email_quoted_printable() {
+    let patch_content = r#"From: Sender <sender@example.com>
+To: list@example.com
+Subject: [PATCH v2 1/2] fix: the =?ISO-8859-1?B?Y2Fm6Q==?=
+Date: Tue, 27 May 2025 19:20:42 +0000
+Message-ID: <20250527192042.1-sender@example.com>
+Content-Type: text/plain; charset=UTF-8
+Content-Transfer-Encoding: quoted-printable
+
+From: =3D?UTF-8?q?Ren=3DC3=3DA9?=3D <rene@example.com>
+
+A long line that is wrapped by the quoted-printable encoding to be shorter=
+ than the limit. Caf=C3=A9.
+
+Reviewed-by: Awiteb <a@4rs.nl>
+Signed-off-by: Ren=C3=A9 <rene@example.com>
+---
+ src/main.rs | 2 +-
+ 1 file changed, 1 insertion(+), 1 deletion(-)
+"#;
+    let patch = GitPatch::from_str(patch_content).unwrap();
+    assert_eq!(patch.subject, "[PATCH v2 1/2] fix: the café");
+    assert_eq!(patch.author.as_deref(), Some("René <rene@example.com>"));
+    assert_eq!(
+        patch.message_id.as_deref(),
+        Some("20250527192042.1-sender@example.com")
+    );
+    assert_eq!(patch.commit, None);
+    assert_eq!(
+        patch.body,
+        "A long line that is wrapped by the quoted-printable encoding to be shorter than the \
+         limit. Café.\n\nReviewed-by: Awiteb <a@4rs.nl>\nSigned-off-by: René <rene@example.com>"
+    );
+    assert_eq!(
+        patch.trailers,
+        vec![
+            ("Reviewed-by".to_owned(), "Awiteb <a@4rs.nl>".to_owned()),
+            (
+                "Signed-off-by".to_owned(),
+                "René <rene@example.com>".to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn public_inbox_mbox() {
+    let mbox = r#"From mboxrd@z Thu Jan  1 00:00:00 1970
+From: Awiteb <a@4rs.nl>
+Subject: [PATCH 1/2] feat: first
+Date: Tue, 27 May 2025 19:20:42 +0000
+Message-Id: <1@4rs.nl>
+
+The line below is escaped
+>From the mboxrd format
+>>From escaped twice
+---
+diff --git a/a b/a
+
+From mboxrd@z Thu Jan  1 00:00:00 1970
+From: Awiteb <a@4rs.nl>
+Subject: [PATCH 2/2] feat: second
+Date: Tue, 27 May 2025 19:20:43 +0000
+Message-Id: <2@4rs.nl>
+In-Reply-To: <1@4rs.nl>
+References: <1@4rs.nl>
+
+Second patch
+---
+diff --git a/b b/b
+"#;
+    let messages = mbox::split_mbox(mbox);
+    assert_eq!(messages.len(), 2);
+
+    let first = GitPatch::from_str(&messages[0]).unwrap();
+    assert_eq!(first.subject, "[PATCH 1/2] feat: first");
+    assert_eq!(
+        first.body,
+        "The line below is escaped\nFrom the mboxrd format\n>From escaped twice"
+    );
+    assert_eq!(first.message_id.as_deref(), Some("1@4rs.nl"));
+    assert!(first.trailers.is_empty());
+
+    let second = GitPatch::from_str(&messages[1]).unwrap();
+    assert_eq!(second.subject, "[PATCH 2/2] feat: second");
+    assert_eq!(second.body, "Second patch");
+}
+
+#[test]
+fn export_and_import_mbox() {
+    use nostr::{
+        event::{Kind, Tag},
+        key::Keys,
+        nips::nip10::Marker,
+    };
+
+    use crate::{cli::test_utils::signed_event, nostr_utils::utils::event_reply_tag};
+
+    let keys = Keys::generate();
+    let patch = |number: u32| {
+        format!(
+            "From 24e8522268ad675996fc3b35209ce23951236bd{number} Mon Sep 17 00:00:00 2001
+From: Awiteb <a@4rs.nl>
+Date: Tue, 27 May 2025 19:20:42 +0000
+Subject: [PATCH {number}/2] feat: patch {number}
+
+>From the body
+---
+diff --git a/a b/a
+"
+        )
+    };
+    let root = signed_event(
+        &keys,
+        Kind::GitPatch,
+        patch(1),
+        [Tag::hashtag(ROOT_HASHTAG_CONTENT)],
+        0,
+    );
+    let second = signed_event(
+        &keys,
+        Kind::GitPatch,
+        patch(2),
+        [
+            event_reply_tag(&root.id, None, Marker::Root),
+            event_reply_tag(&root.id, None, Marker::Reply),
+        ],
+        0,
+    );
+    let addresses = [(keys.public_key(), "Awiteb <_@4rs.nl>".to_owned())].into();
+
+    let exported = export::series_to_mbox(&[root.clone(), second], &addresses).unwrap();
+    let root_message_id = format!("<{}@nostr.invalid>", root.id.to_hex());
+    assert!(exported.contains(&format!("Message-ID: {root_message_id}\n")));
+    assert!(exported.contains(&format!("In-Reply-To: {root_message_id}\n")));
+    assert!(exported.contains(&format!("References: {root_message_id}\n")));
+    assert!(exported.contains("\nFrom: Awiteb <_@4rs.nl>\n"));
+    assert!(exported.contains("\n\nFrom: Awiteb <a@4rs.nl>\n\n>>From the body\n"));
+
+    let reply = "From mboxrd@z Thu Jan  1 00:00:00 1970
+From: Someone <someone@example.com>
+Subject: Re: [PATCH 1/2] feat: patch 1
+
+Looks good
+";
+    let imported = import_mbox::series_from_mbox(&format!("{exported}\n{reply}")).unwrap();
+    assert_eq!(
+        imported
+            .iter()
+            .map(|p| p.subject.as_str())
+            .collect::<Vec<_>>(),
+        ["[PATCH 1/2] feat: patch 1", "[PATCH 2/2] feat: patch 2"]
+    );
+    assert_eq!(imported[0].author.as_deref(), Some("Awiteb <a@4rs.nl>"));
+    assert_eq!(imported[0].body, ">From the body");
+    assert!(imported[1].inner.contains("\nIn-Reply-To: "));
+}
+
 #[test]
 fn normal_patch_filename() {
     let mut patch = GitPatch {
-        inner:   String::new(),
+        inner: String::new(),
         subject: String::new(),
-        body:    String::new(),
+        body: String::new(),
+        ..Default::default()
     };
 
     patch.subject = "[PATCH v2 0/3] feat: Some test just a test".to_owned();
@@ -316,9 +557,10 @@ fn normal_patch_filename() {
 #[test]
 fn patch_filename_without_patch() {
     let mut patch = GitPatch {
-        inner:   String::new(),
+        inner: String::new(),
         subject: "[RFC v5 1/2] Something".to_owned(),
-        body:    String::new(),
+        body: String::new(),
+        ..Default::default()
     };
 
     assert!(patch.filename("").is_err());
@@ -329,9 +571,10 @@ fn patch_filename_without_patch() {
 #[test]
 fn patch_filename_without_number() {
     let mut patch = GitPatch {
-        inner:   String::new(),
+        inner: String::new(),
         subject: "[PATCH v5 /2] Something".to_owned(),
-        body:    String::new(),
+        body: String::new(),
+        ..Default::default()
     };
 
     assert!(patch.filename("").is_err());
@@ -342,10 +585,158 @@ fn patch_filename_without_number() {
 #[test]
 fn patch_filename_without_version() {
     let patch = GitPatch {
-        inner:   String::new(),
+        inner: String::new(),
         subject: "[PATCH 1/2] Something".to_owned(),
-        body:    String::new(),
+        body: String::new(),
+        ..Default::default()
     };
 
     assert!(patch.filename("").is_ok());
 }
+
+#[test]
+fn patch_version_from_subject() {
+    assert_eq!(patch_version("[PATCH] Something"), 1);
+    assert_eq!(patch_version("[PATCH 1/2] Something"), 1);
+    assert_eq!(patch_version("[PATCH v3 0/2] Something"), 3);
+    assert_eq!(patch_version("[PATCH v2] Something"), 2);
+    assert_eq!(patch_version("[RFC PATCH v2 1/3] Something"), 2);
+    assert_eq!(patch_version("[PATCH net-next v4 1/3] Something"), 4);
+    assert_eq!(
+        patch_number_and_total("[RFC PATCH v2 0/3] Something"),
+        Some((0, 3))
+    );
+    assert_eq!(
+        patch_number_and_total("[PATCH net-next 2/3] Something"),
+        Some((2, 3))
+    );
+    assert_eq!(
+        patch_number_and_total("[RFC PATCH] Something"),
+        Some((1, 1))
+    );
+    assert_eq!(patch_number_and_total("[RFC v2 1/3] Something"), None);
+}
+
+#[test]
+fn mbox_series_with_words_around_patch() {
+    let message = |prefix: &str, number: u32| {
+        format!(
+            "From mboxrd@z Thu Jan  1 00:00:00 1970
+From: Awiteb <a@4rs.nl>
+Subject: [{prefix} {number}/2] feat: patch {number}
+Date: Tue, 27 May 2025 19:20:4{number} +0000
+Message-Id: <{number}@4rs.nl>
+
+Patch {number}
+---
+diff --git a/a b/a
+"
+        )
+    };
+
+    for prefix in ["RFC PATCH v2", "PATCH net-next v2"] {
+        let mbox = [
+            message("PATCH", 1),
+            message("PATCH", 2),
+            message(prefix, 1),
+            message(prefix, 2),
+        ]
+        .join("\n");
+        let imported = import_mbox::series_from_mbox(&mbox).unwrap();
+        assert_eq!(
+            imported
+                .iter()
+                .map(|p| p.subject.as_str())
+                .collect::<Vec<_>>(),
+            [
+                format!("[{prefix} 1/2] feat: patch 1"),
+                format!("[{prefix} 2/2] feat: patch 2")
+            ]
+        );
+        assert_eq!(
+            imported[0].filename("").unwrap(),
+            PathBuf::from("v2-0001-feat-patch-1.patch")
+        );
+    }
+}
+
+#[test]
+fn review_comments_per_hunk() {
+    let review = r#"> From 24e8522268ad675996fc3b35209ce23951236bdc Mon Sep 17 00:00:00 2001
+> Subject: [PATCH] chore: a to abc
+>
+Looks good overall
+> ---
+> diff --git a/src/main.rs b/src/main.rs
+> --- a/src/main.rs
+> +++ b/src/main.rs
+> @@ -10,3 +10,3 @@ fn main() {
+>  let a = 1;
+> -let b = 2;
+> +let b = 3;
+Why 3?
+>  let c = 4;
+> diff --git a/README.md b/README.md
+> @@ -1,2 +1,3 @@
+>  # n34
+> +
+>  A CLI
+> -- 
+> 2.49.0"#;
+
+    let comments = review::parse_review(review);
+    assert_eq!(comments.len(), 2);
+    assert_eq!(comments[0].file, None);
+    assert_eq!(comments[0].content, "Looks good overall");
+    assert_eq!(comments[1].file.as_deref(), Some("src/main.rs"));
+    assert_eq!(comments[1].lines, Some((10, 11)));
+    assert_eq!(
+        comments[1].content,
+        "> @@ -10,3 +10,3 @@ fn main() {\n>  let a = 1;\n> -let b = 2;\n> +let b = 3;\nWhy 3?"
+    );
+}
+
+#[test]
+fn review_comment_after_last_hunk() {
+    let review = r#"> diff --git a/README.md b/README.md
+> @@ -1,2 +1,3 @@
+>  # n34
+> +
+>  A CLI
+Unneeded line
+> -- 
+> 2.49.0"#;
+
+    let comments = review::parse_review(review);
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].file.as_deref(), Some("README.md"));
+    assert_eq!(comments[0].lines, Some((1, 3)));
+}
+
+#[test]
+fn render_review_after_its_line() {
+    let patch = r#"diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -10,3 +10,3 @@ fn main() {
+ let a = 1;
+-let b = 2;
++let b = 3;
+ let c = 4;
+diff --git a/README.md b/README.md
+@@ -1,2 +1,3 @@
+ # n34
++
+ A CLI"#;
+    let reviews = [review::InlineReview {
+        file: "src/main.rs".to_owned(),
+        line: 11,
+        text: "Why 3?".to_owned(),
+    }];
+
+    assert_eq!(
+        review::render_reviews(patch, &reviews),
+        "src/main.rs\n@@ -10,3 +10,3 @@ fn main() {\n let a = 1;\n-let b = 2;\n+let b = 3;\n    | \
+         Why 3?\n let c = 4;"
+    );
+}
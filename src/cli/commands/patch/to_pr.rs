@@ -0,0 +1,214 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use std::str::FromStr;
+
+use clap::{ArgGroup, Args};
+use nostr::{event::Tag, hashes::sha1::Hash as Sha1Hash};
+
+use super::GitPatch;
+use crate::{
+    cli::{
+        CliOptions,
+        common_commands,
+        pr,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{EntityType, NaddrOrSet, NostrEvent, PatchPrStatus},
+    },
+    error::{N34Error, N34Result},
+    git_utils,
+    nostr_utils::{
+        NostrClient,
+        traits::{GitPatchUtils, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+#[derive(Debug, Args)]
+#[clap(
+    group(
+        ArgGroup::new("push-or-grasp")
+        .required(true)
+    )
+)]
+pub struct ToPrArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs:   Option<Vec<NaddrOrSet>>,
+    /// The branch to apply the series to. Defaults to a name derived from the
+    /// root patch subject.
+    #[arg(short, long)]
+    branch:   Option<String>,
+    /// The commit or branch to start the branch from. Defaults to the
+    /// `parent-commit` of the first patch if it's in the local repository,
+    /// otherwise `HEAD`.
+    #[arg(long, value_name = "REV")]
+    base:     Option<String>,
+    /// Push the pull request to the repository GRASP server.
+    #[arg(long, group = "push-or-grasp")]
+    grasp:    bool,
+    /// Push the branch to the given git remote or URL, and use it as the
+    /// clone URL.
+    #[arg(long, value_name = "REMOTE", group = "push-or-grasp")]
+    push:     Option<String>,
+    /// The root patch or root revision ID of the series
+    #[arg(value_name = "EVENT-ID")]
+    patch_id: NostrEvent,
+}
+
+impl CommandRunner for ToPrArgs {
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        git_utils::ensure_work_tree()?;
+
+        let naddrs = utils::check_empty_naddrs(utils::naddrs_or_file(
+            self.naddrs.clone().flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?)?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client
+            .add_relays(&[naddrs.extract_relays(), self.patch_id.relays.clone()].concat())
+            .await;
+        let repos = client
+            .fetch_repos(&naddrs.clone().into_coordinates())
+            .await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let patch = client.fetch_patch(self.patch_id.event_id).await?;
+        let (root_id, _) = common_commands::get_patch_root_revision(&patch)?;
+
+        // Fail before making the branch and the PR if the patch can't be closed
+        let root_author = if root_id == patch.id {
+            patch.pubkey
+        } else {
+            client.fetch_patch(root_id).await?.pubkey
+        };
+        let status = client
+            .fetch_patch_status(
+                root_id,
+                None,
+                [
+                    repos.extract_maintainers().as_slice(),
+                    &[root_author],
+                    &naddrs.extract_owners(),
+                ]
+                .concat(),
+            )
+            .await?;
+        super::close::check_can_close(&status)?;
+
+        let events = client.fetch_ordered_patch_series(patch).await?;
+        let patches = events
+            .iter()
+            .map(GitPatch::from_event)
+            .collect::<N34Result<Vec<_>>>()?;
+
+        let branch = match self.branch {
+            Some(branch) => branch,
+            None => super::patch_file_name(&patches[0].subject)?,
+        };
+        let base = match self.base {
+            Some(base) => base,
+            None => {
+                events
+                    .iter()
+                    .zip(&patches)
+                    .find(|(_, patch)| !patch.is_cover_letter())
+                    .and_then(|(event, _)| event.patch_parent_commit())
+                    .filter(|parent| git_utils::commit_object(parent).is_some())
+                    .unwrap_or("HEAD")
+                    .to_owned()
+            }
+        };
+        let commit = apply_series(&patches, &branch, &base)?;
+        let clones = match &self.push {
+            Some(remote) => {
+                Some(vec![pr::push_tip(
+                    remote,
+                    &commit,
+                    Some(&branch),
+                    !options.non_interactive,
+                )?])
+            }
+            None => None,
+        };
+
+        let root = &patches[0];
+        let pr_id = pr::send_pr(
+            &options,
+            naddrs,
+            super::subject_without_prefix(&root.subject).to_owned(),
+            (!root.body.trim().is_empty()).then(|| root.body.clone()),
+            Vec::new(),
+            commit,
+            Some(branch),
+            clones,
+            vec![Tag::event(root_id)],
+        )
+        .await?;
+
+        common_commands::patch_pr_status_command::<{ EntityType::Patch as u8 }>(
+            &options,
+            NostrEvent {
+                event_id: root_id,
+                relays:   self.patch_id.relays,
+            },
+            self.naddrs,
+            PatchPrStatus::Closed,
+            None,
+            Vec::new(),
+            Some(pr_id),
+            None,
+            super::close::check_can_close,
+        )
+        .await
+    }
+}
+
+/// Applies the patch series to a new branch starting from `base`, and returns
+/// the tip of the branch. The branch is deleted if the series doesn't apply
+/// cleanly.
+fn apply_series(patches: &[GitPatch], branch: &str, base: &str) -> N34Result<Sha1Hash> {
+    let original_ref = git_utils::current_ref()?;
+
+    tracing::info!("Applying the series to the branch `{branch}` from `{base}`");
+    git_utils::checkout_new_branch(branch, base)?;
+
+    let mbox = patches
+        .iter()
+        .map(|p| p.inner.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Err(err) = git_utils::am(mbox) {
+        tracing::info!("Cleaning up the branch `{branch}`");
+        git_utils::abort_am(&original_ref, branch)?;
+        return Err(err);
+    }
+
+    let commit = git_utils::rev_parse_commit("HEAD")?;
+    Sha1Hash::from_str(&commit)
+        .map_err(|err| N34Error::GitCommand("rev-parse".to_owned(), err.to_string()))
+}
@@ -0,0 +1,186 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use clap::Args;
+
+use super::{GitPatch, review};
+use crate::{
+    cli::{
+        CliOptions,
+        common_commands,
+        dependencies,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{NaddrOrSet, NostrEvent},
+        verdicts,
+    },
+    error::N34Result,
+    nostr_utils::{
+        NostrClient,
+        traits::{NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct ViewArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs:   Option<Vec<NaddrOrSet>>,
+    /// The root patch or root revision ID
+    #[arg(value_name = "EVENT-ID")]
+    patch_id: NostrEvent,
+}
+
+impl CommandRunner for ViewArgs {
+    const NEED_SIGNER: bool = false;
+
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        let naddrs = utils::naddrs_or_file(
+            self.naddrs.flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client.add_relays(&naddrs.extract_relays()).await;
+        client.add_relays(&self.patch_id.relays).await;
+        let repos = client.fetch_repos(&naddrs.into_coordinates()).await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let patch = client.fetch_patch(self.patch_id.event_id).await?;
+        let (root_id, root_revision) = common_commands::get_patch_root_revision(&patch)?;
+        let authorized_pubkeys = [repos.extract_maintainers().as_slice(), &[patch.pubkey]].concat();
+
+        let status = client
+            .fetch_patch_status(root_id, root_revision, authorized_pubkeys.clone())
+            .await?;
+        let dependencies =
+            dependencies::fetch_dependencies(&client, &patch, &authorized_pubkeys).await?;
+        let series = client.fetch_ordered_patch_series(patch.clone()).await?;
+        let revisions = client.fetch_patch_revisions(root_id).await?;
+        let mut comments = client.fetch_comments(patch.id).await?;
+        let reviews = verdicts::fetch_patch_verdicts(&client, root_id).await?;
+
+        let usernames = client
+            .get_usernames(
+                series
+                    .iter()
+                    .chain(&revisions)
+                    .chain(&comments)
+                    .map(|e| e.pubkey)
+                    .chain(reviews.iter().map(|(reviewer, _)| *reviewer)),
+            )
+            .await;
+
+        let root = GitPatch::from_event(&patch)?;
+        println!(
+            "({status}) {} - [by {}]\n\n{}\n",
+            utils::smart_wrap(&root.subject, 70),
+            usernames[&patch.pubkey],
+            utils::smart_wrap(&root.body, 80)
+        );
+
+        if !dependencies.is_empty() {
+            println!(
+                "Depends on:\n{}\n",
+                utils::format_iter(
+                    dependencies
+                        .iter()
+                        .map(|(e, s)| dependencies::format_dependency(e, s))
+                )
+            );
+        }
+
+        if !reviews.is_empty() {
+            println!(
+                "Reviews of the latest revision:\n{}\n",
+                utils::format_iter(verdicts::format_verdicts(&reviews, &usernames))
+            );
+        }
+
+        println!(
+            "Series:\n{}",
+            utils::format_iter(series.iter().map(super::format_patch_event))
+        );
+
+        if !revisions.is_empty() {
+            println!(
+                "\nRevisions:\n{}",
+                utils::format_iter(revisions.iter().map(|r| {
+                    format!(
+                        "{} - [by {}] at {}",
+                        super::format_patch_event(r),
+                        usernames[&r.pubkey],
+                        utils::format_date(r.created_at)
+                    )
+                }))
+            );
+        }
+
+        for series_patch in &series {
+            let mut reviewed = Vec::new();
+            comments.retain(|comment| {
+                let header = format!(
+                    "{} at {}:",
+                    usernames[&comment.pubkey],
+                    utils::format_date(comment.created_at)
+                );
+                match review::inline_review(comment, series_patch, &header) {
+                    Some(review) => {
+                        reviewed.push(review);
+                        false
+                    }
+                    None => true,
+                }
+            });
+
+            if !reviewed.is_empty() {
+                println!(
+                    "\nReview of {}:\n{}",
+                    super::format_patch_event(series_patch),
+                    review::render_reviews(&series_patch.content, &reviewed)
+                );
+            }
+        }
+
+        if !comments.is_empty() {
+            println!("\nComments:");
+            for comment in comments {
+                println!(
+                    "\n{} at {}:\n{}",
+                    usernames[&comment.pubkey],
+                    utils::format_date(comment.created_at),
+                    utils::smart_wrap(&comment.content, 80)
+                        .lines()
+                        .map(|line| format!("  {line}"))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -50,12 +50,14 @@ pub struct ApplyArgs {
 impl CommandRunner for ApplyArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
         crate::cli::common_commands::patch_pr_status_command::<{ EntityType::PullRequest as u8 }>(
-            options,
+            &options,
             self.pr_id,
             self.naddrs,
             PatchPrStatus::MergedApplied,
             Some(either::Either::Right(self.applied_commits)),
             Vec::new(),
+            None,
+            None,
             |pr_status| {
                 if pr_status.is_merged_or_applied() {
                     return Err(N34Error::InvalidStatus(
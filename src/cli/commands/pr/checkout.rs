@@ -0,0 +1,104 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use clap::Args;
+
+use crate::{
+    cli::{
+        CliOptions,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{NaddrOrSet, NostrEvent},
+    },
+    error::{N34Error, N34Result},
+    git_utils,
+    nostr_utils::{
+        NostrClient,
+        traits::{GitIssuePrMetadata, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct CheckoutArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs: Option<Vec<NaddrOrSet>>,
+    /// The branch to create or update. Defaults to `pr/<branch-name>`, or
+    /// `pr/<short-id>` if the PR has no valid branch name.
+    #[arg(short, long)]
+    branch: Option<String>,
+    /// Overwrite the branch even if it has commits that are not in the PR
+    #[arg(long)]
+    force:  bool,
+    /// Pull request ID
+    #[arg(value_name = "EVENT-ID")]
+    pr_id:  NostrEvent,
+}
+
+impl CommandRunner for CheckoutArgs {
+    const NEED_SIGNER: bool = false;
+
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        git_utils::ensure_work_tree()?;
+
+        let naddrs = utils::naddrs_or_file(
+            self.naddrs.flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client
+            .add_relays(&[naddrs.extract_relays(), self.pr_id.relays].concat())
+            .await;
+        let repos = client.fetch_repos(&naddrs.into_coordinates()).await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let pr = client.fetch_pr(self.pr_id.event_id).await?;
+        let pr_update = client.fetch_pr_update(&pr).await?;
+        let tip = pr_update.as_ref().unwrap_or(&pr);
+        let commit = tip.pr_commit().ok_or(N34Error::PrWithoutCommit)?;
+
+        super::fetch_pr_tip(&pr, tip, commit, &repos)?;
+
+        let branch = self.branch.unwrap_or_else(|| {
+            pr.pr_branch_name()
+                .map(|branch| format!("pr/{branch}"))
+                .filter(|branch| git_utils::is_valid_branch_name(branch))
+                .unwrap_or_else(|| format!("pr/{}", &pr.id.to_hex()[..8]))
+        });
+        if !self.force
+            && git_utils::branch_exists(&branch)?
+            && !git_utils::is_ancestor(&branch, commit)?
+        {
+            return Err(N34Error::BranchDiverged(branch));
+        }
+
+        git_utils::checkout_branch_at(&branch, commit)?;
+        println!("Checked out the pull request tip `{commit}` to the branch `{branch}`");
+
+        Ok(())
+    }
+}
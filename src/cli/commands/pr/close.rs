@@ -46,27 +46,33 @@ pub struct CloseArgs {
 impl CommandRunner for CloseArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
         crate::cli::common_commands::patch_pr_status_command::<{ EntityType::PullRequest as u8 }>(
-            options,
+            &options,
             self.pr_id,
             self.naddrs,
             PatchPrStatus::Closed,
             None,
             Vec::new(),
-            |pr_status| {
-                if pr_status.is_closed() {
-                    return Err(N34Error::InvalidStatus(
-                        "You can't close an already closed pull request".to_owned(),
-                    ));
-                }
-
-                if pr_status.is_merged_or_applied() {
-                    return Err(N34Error::InvalidStatus(
-                        "You can't close a merged/applied pull request".to_owned(),
-                    ));
-                }
-                Ok(())
-            },
+            None,
+            None,
+            check_can_close,
         )
         .await
     }
 }
+
+/// Checks if the pull request can be closed, it must not be closed or
+/// merged/applied.
+pub fn check_can_close(pr_status: &PatchPrStatus) -> N34Result<()> {
+    if pr_status.is_closed() {
+        return Err(N34Error::InvalidStatus(
+            "You can't close an already closed pull request".to_owned(),
+        ));
+    }
+
+    if pr_status.is_merged_or_applied() {
+        return Err(N34Error::InvalidStatus(
+            "You can't close a merged/applied pull request".to_owned(),
+        ));
+    }
+    Ok(())
+}
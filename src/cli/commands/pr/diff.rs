@@ -0,0 +1,126 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use clap::Args;
+use nostr::{event::Event, nips::nip34::GitRepositoryAnnouncement};
+
+use crate::{
+    cli::{
+        CliOptions,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{NaddrOrSet, NostrEvent},
+    },
+    error::{N34Error, N34Result},
+    git_utils,
+    nostr_utils::{
+        NostrClient,
+        traits::{GitIssuePrMetadata, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs: Option<Vec<NaddrOrSet>>,
+    /// Show the diffstat instead of the diff
+    #[arg(long)]
+    stat:   bool,
+    /// Diff from the tip of this update, or the pull request itself, instead
+    /// of the merge base with the repository `HEAD`.
+    #[arg(long, value_name = "EVENT-ID")]
+    from:   Option<NostrEvent>,
+    /// Diff to the tip of this update, or the pull request itself, instead of
+    /// the latest tip.
+    #[arg(long, value_name = "EVENT-ID")]
+    to:     Option<NostrEvent>,
+    /// Pull request ID
+    #[arg(value_name = "EVENT-ID")]
+    pr_id:  NostrEvent,
+}
+
+impl CommandRunner for DiffArgs {
+    const NEED_SIGNER: bool = false;
+
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        git_utils::ensure_work_tree()?;
+
+        let naddrs = utils::naddrs_or_file(
+            self.naddrs.flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client
+            .add_relays(&[naddrs.extract_relays(), self.pr_id.relays].concat())
+            .await;
+        let coordinates = naddrs.into_coordinates();
+        let repos = client.fetch_repos(&coordinates).await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let pr = client.fetch_pr(self.pr_id.event_id).await?;
+        let mut revisions = vec![pr.clone()];
+        revisions.extend(client.fetch_pr_updates(&pr).await?);
+
+        let to = match self.to {
+            Some(to) => find_revision(&revisions, &to)?,
+            None => revisions.last().expect("The PR is there"),
+        };
+        let to_commit = fetch_revision_tip(&pr, to, &repos)?;
+
+        let from_commit = match self.from {
+            Some(from) => fetch_revision_tip(&pr, find_revision(&revisions, &from)?, &repos)?,
+            None => {
+                let head = super::repo_head(&client, &coordinates, &repos).await?;
+                git_utils::merge_base(&head, &to_commit)?
+            }
+        };
+
+        println!("{}", git_utils::diff(&from_commit, &to_commit, self.stat)?);
+        Ok(())
+    }
+}
+
+/// Returns the PR revision, the PR itself or one of its updates, with the
+/// given ID.
+fn find_revision<'a>(revisions: &'a [Event], event: &NostrEvent) -> N34Result<&'a Event> {
+    revisions
+        .iter()
+        .find(|revision| revision.id == event.event_id)
+        .ok_or_else(|| N34Error::NotPrRevision(event.event_id.to_hex()))
+}
+
+/// Fetches the tip of the PR revision to the local repository and returns it.
+fn fetch_revision_tip(
+    pr: &Event,
+    revision: &Event,
+    repos: &Vec<GitRepositoryAnnouncement>,
+) -> N34Result<String> {
+    let commit = revision.pr_commit().ok_or(N34Error::PrWithoutCommit)?;
+    super::fetch_pr_tip(pr, revision, commit, repos)?;
+    Ok(commit.to_owned())
+}
@@ -46,12 +46,14 @@ pub struct DraftArgs {
 impl CommandRunner for DraftArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
         crate::cli::common_commands::patch_pr_status_command::<{ EntityType::PullRequest as u8 }>(
-            options,
+            &options,
             self.pr_id,
             self.naddrs,
             PatchPrStatus::Draft,
             None,
             Vec::new(),
+            None,
+            None,
             |pr_status| {
                 if pr_status.is_drafted() {
                     return Err(N34Error::InvalidStatus(
@@ -20,10 +20,19 @@ use nostr::hashes::sha1::Hash as Sha1Hash;
 use crate::{
     cli::{
         CliOptions,
-        traits::CommandRunner,
+        common_commands,
+        dependencies,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
         types::{EntityType, NaddrOrSet, NostrEvent, PatchPrStatus},
+        verdicts,
     },
     error::{N34Error, N34Result},
+    git_utils,
+    nostr_utils::{
+        NostrClient,
+        traits::{GitIssuePrMetadata, NaddrsUtils, ReposUtils},
+        utils,
+    },
 };
 
 #[derive(Debug, Args)]
@@ -42,19 +51,63 @@ pub struct MergeArgs {
     naddrs:       Option<Vec<NaddrOrSet>>,
     /// The open PR id to merge it.
     pr_id:        NostrEvent,
-    /// The merge commit id
-    merge_commit: Sha1Hash,
+    /// The merge commit id. Defaults to the pull request tip if it's on the
+    /// first-parent chain of `HEAD`, or the first merge on `HEAD` that merged
+    /// it.
+    merge_commit: Option<Sha1Hash>,
+    /// Merge even if the merge policy is not satisfied. The override is
+    /// recorded in the status content
+    #[arg(long)]
+    force:        bool,
 }
 
 impl CommandRunner for MergeArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
-        crate::cli::common_commands::patch_pr_status_command::<{ EntityType::PullRequest as u8 }>(
-            options,
+        git_utils::ensure_work_tree()?;
+
+        let naddrs = utils::naddrs_or_file(
+            self.naddrs.clone().flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client
+            .add_relays(&[naddrs.extract_relays(), self.pr_id.relays.clone()].concat())
+            .await;
+        let repos = client
+            .fetch_repos(&naddrs.clone().into_coordinates())
+            .await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let pr = client.fetch_pr(self.pr_id.event_id).await?;
+        let pr_update = client.fetch_pr_update(&pr).await?;
+        let tip = pr_update.as_ref().unwrap_or(&pr);
+        let commit = tip.pr_commit().ok_or(N34Error::PrWithoutCommit)?;
+        super::fetch_pr_tip(&pr, tip, commit, &repos)?;
+        let merge_commit = common_commands::verify_merge_commit(self.merge_commit, commit)?;
+        let note = match &options.config.merge_policy {
+            Some(policy) => {
+                verdicts::check_merge_policy(
+                    policy,
+                    &verdicts::fetch_pr_verdicts(&client, &pr).await?,
+                    &[naddrs.extract_owners(), repos.extract_maintainers()].concat(),
+                    &pr.pubkey,
+                    self.force,
+                )?
+            }
+            None => None,
+        };
+
+        common_commands::patch_pr_status_command::<{ EntityType::PullRequest as u8 }>(
+            &options,
             self.pr_id,
-            self.naddrs,
+            self.naddrs.clone(),
             PatchPrStatus::MergedApplied,
-            Some(either::Either::Left(self.merge_commit)),
+            Some(either::Either::Left(merge_commit)),
             Vec::new(),
+            None,
+            note,
             |pr_status| {
                 if pr_status.is_merged_or_applied() {
                     return Err(N34Error::InvalidStatus(
@@ -77,6 +130,8 @@ impl CommandRunner for MergeArgs {
                 Ok(())
             },
         )
-        .await
+        .await?;
+
+        dependencies::handle_dependants(&options, self.naddrs, &pr, &merge_commit).await
     }
 }
@@ -16,8 +16,14 @@
 
 /// `pr apply` suubcommand
 mod apply;
+/// `pr assign` subcommand
+mod assign;
+/// `pr checkout` subcommand
+mod checkout;
 /// `pr close` subcommand
 mod close;
+/// `pr diff` subcommand
+mod diff;
 /// `pr draft` subcommand
 mod draft;
 /// `pr list` subcommand
@@ -28,25 +34,55 @@ mod merge;
 mod new;
 /// `pr reopen` subcommand
 mod reopen;
+/// `pr review` subcommand
+mod review;
+#[cfg(test)]
+mod tests;
+/// `pr to-patches` subcommand
+mod to_patches;
+/// `pr unassign` subcommand
+mod unassign;
 /// `pr update` subcommand
 mod update;
 /// `pr view` subcommand
 mod view;
 
+use std::{collections::HashMap, str::FromStr};
+
 use clap::Subcommand;
+use nostr::{
+    event::{Event, EventId},
+    hashes::sha1::Hash as Sha1Hash,
+    key::PublicKey,
+    nips::{nip01::Coordinate, nip34::GitRepositoryAnnouncement},
+    types::Url,
+};
 
 use self::apply::ApplyArgs;
+use self::assign::AssignArgs;
+use self::checkout::CheckoutArgs;
 use self::close::CloseArgs;
+use self::diff::DiffArgs;
 use self::draft::DraftArgs;
 use self::list::ListArgs;
 use self::merge::MergeArgs;
 use self::new::NewArgs;
+pub use self::new::send_pr;
 use self::reopen::ReopenArgs;
+use self::review::ReviewArgs;
+use self::to_patches::ToPatchesArgs;
+use self::unassign::UnassignArgs;
 use self::update::UpdateArgs;
 use self::view::ViewArgs;
 use crate::{
-    cli::{CliOptions, traits::CommandRunner},
-    error::N34Result,
+    cli::{CliOptions, traits::CommandRunner, types::PatchPrStatus, verdicts::ReviewVerdict},
+    error::{N34Error, N34Result},
+    git_utils,
+    nostr_utils::{
+        NostrClient,
+        traits::{GitIssuePrMetadata, IsGraspClone, ReposUtils},
+        utils,
+    },
 };
 
 /// The kind of the pull request
@@ -63,6 +99,16 @@ pub enum PrSubcommands {
     Update(UpdateArgs),
     /// View a pull request.
     View(ViewArgs),
+    /// Fetch the pull request tip into a local branch.
+    Checkout(CheckoutArgs),
+    /// Show the changes introduced by a pull request.
+    Diff(DiffArgs),
+    /// Review a pull request with a verdict.
+    Review(ReviewArgs),
+    /// Assign users to a pull request.
+    Assign(AssignArgs),
+    /// Unassign users from a pull request.
+    Unassign(UnassignArgs),
     /// List pull requests.
     List(ListArgs),
     /// Close a pull request.
@@ -75,10 +121,255 @@ pub enum PrSubcommands {
     Apply(ApplyArgs),
     /// Merge a pull request.
     Merge(MergeArgs),
+    /// Convert a pull request to a patch series.
+    ToPatches(ToPatchesArgs),
 }
 
 impl CommandRunner for PrSubcommands {
     async fn run(self, options: CliOptions) -> N34Result<()> {
-        crate::run_command!(self, options, & New Update View List Close Draft Reopen Apply Merge)
+        crate::run_command!(self, options, & New Update View Checkout Diff Review Assign Unassign List Close Draft Reopen Apply Merge ToPatches)
+    }
+}
+
+/// Returns the commit at the tip of the PR. The revision is resolved in the
+/// local repository, defaulting to `HEAD`. A SHA-1 hash is used as is outside
+/// a git repository.
+fn resolve_tip(rev: Option<&str>) -> N34Result<Sha1Hash> {
+    if let Some(rev) = rev
+        && git_utils::ensure_work_tree().is_err()
+    {
+        return Sha1Hash::from_str(rev).map_err(|_| N34Error::NotGitRepo);
+    }
+
+    git_utils::ensure_work_tree()?;
+    let commit = git_utils::rev_parse_commit(rev.unwrap_or("HEAD"))?;
+    Sha1Hash::from_str(&commit)
+        .map_err(|err| N34Error::GitCommand("rev-parse".to_owned(), err.to_string()))
+}
+
+/// Pushes the PR tip to the branch of the given remote, and returns the remote
+/// URL to use as the PR clone.
+pub fn push_tip(
+    remote: &str,
+    commit: &Sha1Hash,
+    branch: Option<&str>,
+    interactive: bool,
+) -> N34Result<nostr::Url> {
+    let branch = branch.ok_or(N34Error::PushBranchNotFound)?;
+    let url = git_utils::remote_url(remote)?;
+    let clone = nostr::Url::parse(&url).map_err(|_| N34Error::InvalidCloneUrl(url.clone()))?;
+
+    tracing::info!("Pushing `{commit}` to the branch `{branch}` of `{url}`");
+    git_utils::push(
+        remote,
+        &commit.to_string(),
+        &format!("refs/heads/{branch}"),
+        interactive,
+    )?;
+    Ok(clone)
+}
+
+/// Infers the PR subject and body from the commits since the merge base of the
+/// tip with the default branch.
+fn subject_and_body_from_log(
+    commit: &Sha1Hash,
+    branch: Option<&str>,
+) -> N34Result<(String, Option<String>)> {
+    let commit = commit.to_string();
+    let range = match git_utils::default_branch()
+        .and_then(|default| git_utils::merge_base(&default, &commit))
+    {
+        Ok(base) => format!("{base}..{commit}"),
+        Err(err) => {
+            tracing::warn!("Can't find the merge base, using the tip commit only: {err}");
+            format!("{commit}^!")
+        }
+    };
+
+    pr_subject_and_body(&git_utils::commit_messages(&range)?, branch).ok_or_else(|| {
+        N34Error::InvalidEvent(format!(
+            "No commits in `{range}` to take the subject from, use `--subject` or `--editor`"
+        ))
+    })
+}
+
+/// Returns the PR subject and body from the commit messages, oldest first. A
+/// single commit gives its subject and body, otherwise the subject is taken
+/// from the branch name and the body lists the commits.
+fn pr_subject_and_body(
+    messages: &[String],
+    branch: Option<&str>,
+) -> Option<(String, Option<String>)> {
+    let split_message = |message: &str| {
+        let (subject, body) = message.split_once("\n\n").unwrap_or((message, ""));
+        (subject.replace('\n', " "), body.trim().to_owned())
+    };
+
+    match messages {
+        [] => None,
+        [message] => {
+            let (subject, body) = split_message(message);
+            Some((subject, (!body.is_empty()).then_some(body)))
+        }
+        messages => {
+            let commits = messages
+                .iter()
+                .map(|message| format!("- {}", split_message(message).0))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let subject = match branch {
+                Some(branch) => {
+                    let subject = branch.replace(['-', '_', '/'], " ");
+                    let mut chars = subject.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().chain(chars).collect())
+                        .unwrap_or_default()
+                }
+                None => split_message(&messages[0]).0,
+            };
+            Some((subject, Some(commits)))
+        }
+    }
+}
+
+/// Fetches the PR tip commit, trying each clone URL of the `tip` event in turn,
+/// then the clone URLs of the original `pr` and of the repositories. For GRASP
+/// clones the `refs/nostr/<event-id>` ref is tried first. Fails if the commit
+/// can't be fetched from any of them. Nothing is fetched if the commit is
+/// already in the local repository.
+fn fetch_pr_tip(
+    pr: &Event,
+    tip: &Event,
+    commit: &str,
+    repos: &Vec<GitRepositoryAnnouncement>,
+) -> N34Result<()> {
+    if git_utils::rev_parse_commit(commit).is_ok() {
+        tracing::info!("The pull request tip `{commit}` is already in the local repository");
+        return Ok(());
+    }
+
+    let mut clones = Vec::new();
+    for clone in tip
+        .pr_clones()
+        .into_iter()
+        .chain(pr.pr_clones())
+        .chain(repos.extract_clones().into_iter().cloned())
+    {
+        if !clones.contains(&clone) {
+            clones.push(clone);
+        }
+    }
+
+    for clone in clones {
+        for refspec in clone_refspecs(&clone, &tip.id, pr.pr_branch_name(), commit) {
+            tracing::info!("Fetching `{refspec}` from `{clone}`");
+            match git_utils::fetch(clone.as_str(), &refspec) {
+                Ok(fetched) if fetched == commit => return Ok(()),
+                Ok(fetched) => {
+                    tracing::warn!(
+                        "`{refspec}` in `{clone}` points to `{fetched}` instead of the PR tip \
+                         `{commit}`"
+                    );
+                }
+                Err(err) => tracing::debug!("{err}"),
+            }
+        }
+    }
+
+    Err(N34Error::CanNotFetchPrTip(commit.to_owned()))
+}
+
+/// Returns the refspecs to try fetching the PR tip from the clone URL
+fn clone_refspecs(
+    clone: &Url,
+    event_id: &EventId,
+    branch_name: Option<&str>,
+    commit: &str,
+) -> Vec<String> {
+    let mut refspecs = Vec::new();
+    if clone.is_grasp_clone() {
+        refspecs.push(format!("refs/nostr/{}", event_id.to_hex()));
     }
+    refspecs.push(commit.to_owned());
+    if let Some(branch) = branch_name.filter(|branch| git_utils::is_valid_branch_name(branch)) {
+        refspecs.push(format!("refs/heads/{branch}"));
+    }
+    refspecs
+}
+
+/// Returns the commit of the repository `HEAD`. It's taken from the repository
+/// state announcement, fetched from the repository clones if it's not in the
+/// local repository, falling back to the local default branch.
+pub async fn repo_head(
+    client: &NostrClient,
+    coordinates: &[Coordinate],
+    repos: &Vec<GitRepositoryAnnouncement>,
+) -> N34Result<String> {
+    if let Some(head) = client
+        .fetch_repo_head(coordinates, &repos.extract_maintainers())
+        .await?
+    {
+        if git_utils::rev_parse_commit(&head).is_ok() {
+            return Ok(head);
+        }
+        for clone in repos.extract_clones() {
+            tracing::info!("Fetching the repository `HEAD` `{head}` from `{clone}`");
+            match git_utils::fetch(clone.as_str(), &head) {
+                Ok(fetched) if fetched == head => return Ok(head),
+                Ok(_) => {}
+                Err(err) => tracing::debug!("{err}"),
+            }
+        }
+        tracing::warn!("Can't fetch the repository `HEAD` `{head}`, using the default branch");
+    }
+
+    git_utils::default_branch()
+}
+
+/// Formats the PR timeline, its opening and updates with their tips, the
+/// status changes and the review verdicts, ordered by their creation time.
+/// Events of users without a name in `usernames` are attributed to `Unknown`.
+///
+/// The comments content is not included, it's shown in the comments threads.
+pub fn format_timeline(
+    pr: &Event,
+    updates: &[Event],
+    statuses: &[Event],
+    comments: &[Event],
+    usernames: &HashMap<PublicKey, String>,
+) -> String {
+    let tip = |event: &Event| event.pr_commit().unwrap_or("N/A").to_owned();
+
+    let mut entries = vec![(pr, format!("opened the pull request at `{}`", tip(pr)))];
+    entries.extend(
+        updates
+            .iter()
+            .map(|update| (update, format!("updated the tip to `{}`", tip(update)))),
+    );
+    entries.extend(statuses.iter().filter_map(|status| {
+        let new_status = PatchPrStatus::try_from(status.kind).ok()?;
+        Some((status, format!("changed the status to {new_status}")))
+    }));
+    entries.extend(comments.iter().filter_map(|comment| {
+        ReviewVerdict::from_event(comment)
+            .filter(|verdict| !matches!(verdict, ReviewVerdict::Comment))
+            .map(|verdict| (comment, verdict.past_tense().to_owned()))
+    }));
+    entries.sort_by_key(|(event, _)| event.created_at);
+
+    entries
+        .into_iter()
+        .map(|(event, action)| {
+            format!(
+                "{} - {} {action}",
+                utils::format_date(event.created_at),
+                usernames
+                    .get(&event.pubkey)
+                    .map(String::as_str)
+                    .unwrap_or("Unknown"),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
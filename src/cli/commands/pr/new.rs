@@ -18,18 +18,21 @@ use std::iter;
 
 use clap::{ArgGroup, Args};
 use nostr::{
-    event::{EventBuilder, Tag, TagKind, TagStandard},
+    event::{EventBuilder, EventId, Tag, TagKind, TagStandard},
     filter::Alphabet,
     hashes::sha1::Hash as Sha1Hash,
+    nips::nip19::Nip19Coordinate,
 };
 
 use crate::{
     cli::{
         CliOptions,
-        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
-        types::NaddrOrSet,
+        parsers,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt, VecNostrEventExt},
+        types::{NaddrOrSet, NostrEvent},
     },
     error::N34Result,
+    git_utils,
     nostr_utils::{
         NostrClient,
         traits::{NaddrsUtils, ReposUtils},
@@ -41,7 +44,6 @@ use crate::{
 #[clap(
     group(
         ArgGroup::new("pr-subject")
-        .required(true)
     ),
     group(
         ArgGroup::new("clone-or-grasp")
@@ -60,135 +62,211 @@ pub struct NewArgs {
         long = "repo",
         value_delimiter = ','
     )]
-    naddrs:  Option<Vec<NaddrOrSet>>,
+    naddrs:     Option<Vec<NaddrOrSet>>,
     /// The body content of the pull request. Cannot be used together with the
     /// `--editor` flag.
     #[arg(long, group = "pr-body")]
-    body:    Option<String>,
+    body:       Option<String>,
     /// The subject or title of the pull request. Cannot be used together with
     /// the `--editor` flag.
+    ///
+    /// If omitted with the body, they are taken from the commits since the
+    /// merge base with the default branch.
     #[arg(long, group = "pr-subject")]
-    subject: Option<String>,
+    subject:    Option<String>,
     /// Opens the user's default editor to write PR subject and body.
     ///
     /// The first line will be used as the issue subject.
     #[arg(short, long, group = "pr-subject", group = "pr-body")]
-    editor:  bool,
+    editor:     bool,
     /// Labels to associate with the pull request, separated by commas.
     #[arg(long, value_delimiter = ',')]
-    labels:  Vec<String>,
-    /// The branch name for the pull request.
+    labels:     Vec<String>,
+    /// The branch name for the pull request. Defaults to the current branch.
     #[arg(long)]
-    branch:  Option<String>,
+    branch:     Option<String>,
+    /// The pull request or patch this one is built on top of. Can be given
+    /// multiple times.
+    #[arg(long, value_name = "EVENT-ID")]
+    depends_on: Vec<NostrEvent>,
     /// Push the pull request to the repository GRASP server.
     #[arg(long, group = "clone-or-grasp")]
-    grasp:   bool,
-    /// The SHA-1 hash of the commit at the tip of the PR branch.
-    ///
-    /// You can get it using `git rev-parse <branch-name>`
-    commit:  Sha1Hash,
+    grasp:      bool,
+    /// Push the branch to the given git remote or URL before creating the
+    /// pull request, and use it as the clone URL.
+    #[arg(long, value_name = "REMOTE", group = "clone-or-grasp")]
+    push:       Option<String>,
+    /// The commit at the tip of the PR branch, a revision or a SHA-1 hash.
+    /// Defaults to `HEAD`.
+    #[arg(long, value_name = "REV")]
+    commit:     Option<String>,
     /// Repositories to clone for the pull request, separated by commas.
-    #[arg(value_delimiter = ',', group = "clone-or-grasp")]
-    clones:  Vec<nostr::Url>,
+    #[arg(
+        value_delimiter = ',',
+        value_parser = parsers::parse_clone_url,
+        group = "clone-or-grasp"
+    )]
+    clones:     Vec<nostr::Url>,
 }
 
 impl CommandRunner for NewArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
+        let commit = super::resolve_tip(self.commit.as_deref())?;
+        let branch = self.branch.or_else(git_utils::current_branch);
+        let (subject, body) = if self.subject.is_none() && !self.editor {
+            let (subject, body) = super::subject_and_body_from_log(&commit, branch.as_deref())?;
+            (subject, self.body.or(body))
+        } else {
+            utils::subject_and_body(self.subject, self.body, ".md")?
+        };
+        let clones = match &self.push {
+            Some(remote) => {
+                Some(vec![super::push_tip(
+                    remote,
+                    &commit,
+                    branch.as_deref(),
+                    !options.non_interactive,
+                )?])
+            }
+            None if self.grasp => None,
+            None => Some(self.clones),
+        };
+
         let naddrs = utils::check_empty_naddrs(utils::naddrs_or_file(
             self.naddrs.flat_naddrs(&options.config.sets)?,
             &utils::nostr_address_path()?,
         )?)?;
-        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
-        let client = NostrClient::init(&options, &relays).await;
-        let naddrs_relays = naddrs.extract_relays();
-        client.add_relays(&naddrs_relays).await;
-        let coordinates = naddrs.into_coordinates();
-        let repos = client.fetch_repos(coordinates.as_slice()).await?;
-        let maintainers = repos.extract_maintainers();
-        let repos_relays = repos.extract_relays();
-        client.add_relays(&repos_relays).await;
-        let user_pubk = client.pubkey().await?;
-        let relays_list = client.user_relays_list(user_pubk).await?;
-        client
-            .add_relays(&utils::add_read_relays(relays_list.as_ref()))
-            .await;
-
-        let (subject, body) = utils::subject_and_body(self.subject, self.body, ".md")?;
-        let body_details = if let Some(body) = &body {
-            Some(client.parse_content(body).await)
-        } else {
-            None
-        };
 
-        let mut event_builder = EventBuilder::new(super::PR_KIND, body.unwrap_or_default())
-            .dedup_tags()
-            .pow(options.pow.unwrap_or_default())
-            .tags(
-                coordinates
-                    .into_iter()
-                    .map(|c| Tag::coordinate(c, repos_relays.first().cloned())),
-            )
-            .tags(maintainers.iter().map(|p| Tag::public_key(*p)))
-            .tags(
-                body_details
-                    .clone()
-                    .map(|c| c.into_tags())
-                    .unwrap_or_default(),
-            )
-            .tag(Tag::from_standardized_without_cell(TagStandard::Subject(
-                subject,
-            )))
-            .tags(self.labels.into_iter().map(Tag::hashtag))
-            .tag(Tag::custom(
-                TagKind::single_letter(Alphabet::C, false),
-                iter::once(self.commit.to_string()),
-            ));
-
-        if let Some(euc) = repos.extract_euc() {
-            event_builder = event_builder.tag(Tag::reference(euc.to_string()))
-        }
-
-        if let Some(branch) = self.branch {
-            event_builder = event_builder.tag(Tag::custom(
-                TagKind::custom("branch-name"),
-                iter::once(branch),
-            ));
-        }
+        send_pr(
+            &options,
+            naddrs,
+            subject,
+            body,
+            self.labels,
+            commit,
+            branch,
+            clones,
+            self.depends_on.into_depends_on_tags(),
+        )
+        .await?;
+        Ok(())
+    }
+}
 
-        let event = if self.grasp {
-            utils::build_grasp_event(&repos, user_pubk, event_builder.clone())?
-        } else {
-            // Since `grasp` is false, `clones` must be provided
-            event_builder = event_builder.tag(Tag::custom(
-                TagKind::custom("clone"),
-                self.clones.iter().map(ToString::to_string),
-            ));
-            event_builder.build(user_pubk)
-        };
+/// Publishes a new pull request to the repositories and returns its ID. If
+/// `clones` is `None`, the tip is pushed to the repositories GRASP servers
+/// and the accepting server is used as the clone.
+#[allow(clippy::too_many_arguments)]
+pub async fn send_pr(
+    options: &CliOptions,
+    naddrs: Vec<Nip19Coordinate>,
+    subject: String,
+    body: Option<String>,
+    labels: Vec<String>,
+    commit: Sha1Hash,
+    branch: Option<String>,
+    clones: Option<Vec<nostr::Url>>,
+    extra_tags: Vec<Tag>,
+) -> N34Result<EventId> {
+    let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+    let client = NostrClient::init(options, &relays).await;
+    let naddrs_relays = naddrs.extract_relays();
+    client.add_relays(&naddrs_relays).await;
+    let coordinates = naddrs.into_coordinates();
+    let repos = client.fetch_repos(coordinates.as_slice()).await?;
+    let maintainers = repos.extract_maintainers();
+    let repos_relays = repos.extract_relays();
+    client.add_relays(&repos_relays).await;
+    let user_pubk = client.pubkey().await?;
+    let relays_list = client.user_relays_list(user_pubk).await?;
+    client
+        .add_relays(&utils::add_read_relays(relays_list.as_ref()))
+        .await;
 
-        let event_id = event.id.expect("There is an id");
+    let body_details = if let Some(body) = &body {
+        Some(client.parse_content(body).await)
+    } else {
+        None
+    };
 
-        let write_relays = [
-            relays,
-            repos_relays,
-            naddrs_relays,
-            utils::add_write_relays(relays_list.as_ref()),
-            // Include read relays for each maintainer (if found)
-            client.read_relays_from_users(&maintainers).await,
+    let mut event_builder = EventBuilder::new(super::PR_KIND, body.unwrap_or_default())
+        .dedup_tags()
+        .pow(options.pow.unwrap_or_default())
+        .tags(
+            coordinates
+                .into_iter()
+                .map(|c| Tag::coordinate(c, repos_relays.first().cloned())),
+        )
+        .tags(maintainers.iter().map(|p| Tag::public_key(*p)))
+        .tags(
             body_details
-                .map(|c| c.write_relays.into_iter().collect())
+                .clone()
+                .map(|c| c.into_tags())
                 .unwrap_or_default(),
-        ]
-        .concat();
+        )
+        .tag(Tag::from_standardized_without_cell(TagStandard::Subject(
+            subject,
+        )))
+        .tags(labels.into_iter().map(Tag::hashtag))
+        .tag(Tag::custom(
+            TagKind::single_letter(Alphabet::C, false),
+            iter::once(commit.to_string()),
+        ))
+        .tags(extra_tags);
 
-        tracing::trace!(relays = ?write_relays, "Write relays list");
-        let success = client
-            .send_event_to(event, relays_list.as_ref(), &write_relays)
-            .await?;
-
-        let nevent = utils::new_nevent(event_id, &success)?;
-        println!("Pull request created: {nevent}");
+    if let Some(euc) = repos.extract_euc() {
+        event_builder = event_builder.tag(Tag::reference(euc.to_string()))
+    }
 
-        Ok(())
+    if let Some(branch) = branch {
+        event_builder = event_builder.tag(Tag::custom(
+            TagKind::custom("branch-name"),
+            iter::once(branch),
+        ));
     }
+
+    let event = match clones {
+        Some(clones) => {
+            event_builder
+                .tag(Tag::custom(
+                    TagKind::custom("clone"),
+                    clones.iter().map(ToString::to_string),
+                ))
+                .build(user_pubk)
+        }
+        None => {
+            utils::build_grasp_event(
+                &repos,
+                user_pubk,
+                event_builder,
+                &commit.to_string(),
+                !options.non_interactive,
+            )?
+        }
+    };
+
+    let event_id = event.id.expect("There is an id");
+
+    let write_relays = [
+        relays,
+        repos_relays,
+        naddrs_relays,
+        utils::add_write_relays(relays_list.as_ref()),
+        // Include read relays for each maintainer (if found)
+        client.read_relays_from_users(&maintainers).await,
+        body_details
+            .map(|c| c.write_relays.into_iter().collect())
+            .unwrap_or_default(),
+    ]
+    .concat();
+
+    tracing::trace!(relays = ?write_relays, "Write relays list");
+    let success = client
+        .send_event_to(event, relays_list.as_ref(), &write_relays)
+        .await?;
+
+    let nevent = utils::new_nevent(event_id, &success)?;
+    println!("Pull request created: {nevent}");
+
+    Ok(event_id)
 }
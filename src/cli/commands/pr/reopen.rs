@@ -46,12 +46,14 @@ pub struct ReopenArgs {
 impl CommandRunner for ReopenArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
         crate::cli::common_commands::patch_pr_status_command::<{ EntityType::PullRequest as u8 }>(
-            options,
+            &options,
             self.pr_id,
             self.naddrs,
             PatchPrStatus::Open,
             None,
             Vec::new(),
+            None,
+            None,
             |pr_status| {
                 if pr_status.is_open() {
                     return Err(N34Error::InvalidStatus(
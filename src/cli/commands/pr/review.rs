@@ -0,0 +1,151 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use clap::{ArgGroup, Args};
+use nostr::{
+    event::{EventBuilder, Tag, TagKind},
+    filter::Alphabet,
+};
+
+use crate::{
+    cli::{
+        CliOptions,
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{NaddrOrSet, NostrEvent},
+        verdicts::ReviewVerdict,
+    },
+    error::{N34Error, N34Result},
+    nostr_utils::{
+        NostrClient,
+        traits::{GitIssuePrMetadata, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+#[derive(Args, Debug)]
+#[clap(
+    group(
+        ArgGroup::new("verdict")
+            .args(["approve", "request_changes", "comment"])
+            .required(true)
+    ),
+    group(
+        ArgGroup::new("review-content")
+            .args(["message", "editor"])
+    )
+)]
+pub struct ReviewArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs:          Option<Vec<NaddrOrSet>>,
+    /// Approve the current tip of the pull request
+    #[arg(long)]
+    approve:         bool,
+    /// Request changes to the current tip of the pull request
+    #[arg(long)]
+    request_changes: bool,
+    /// Review the current tip of the pull request without a verdict
+    #[arg(long)]
+    comment:         bool,
+    /// The review message. Cannot be used together with the `--editor` flag
+    #[arg(short, long)]
+    message:         Option<String>,
+    /// Opens the user's default editor to write the review message
+    #[arg(short, long)]
+    editor:          bool,
+    /// Pull request ID
+    #[arg(value_name = "EVENT-ID")]
+    pr_id:           NostrEvent,
+}
+
+impl CommandRunner for ReviewArgs {
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        let verdict = ReviewVerdict::from_flags(self.approve, self.request_changes, self.comment)
+            .expect("The verdict group is required");
+        let content = match verdict.default_content() {
+            Some(content) if self.message.is_none() && !self.editor => content.to_owned(),
+            _ => utils::get_content(self.message.as_ref(), None::<&str>, ".txt")?,
+        };
+
+        let naddrs = utils::check_empty_naddrs(utils::naddrs_or_file(
+            self.naddrs.flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?)?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+        let user_pubk = client.pubkey().await?;
+
+        client
+            .add_relays(&[naddrs.extract_relays(), self.pr_id.relays].concat())
+            .await;
+        let relays_list = client.user_relays_list(user_pubk).await?;
+        client
+            .add_relays(&utils::add_read_relays(relays_list.as_ref()))
+            .await;
+        let repos = client.fetch_repos(&naddrs.into_coordinates()).await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let pr = client.fetch_pr(self.pr_id.event_id).await?;
+        let pr_update = client.fetch_pr_update(&pr).await?;
+        let commit = pr_update
+            .as_ref()
+            .unwrap_or(&pr)
+            .pr_commit()
+            .ok_or(N34Error::PrWithoutCommit)?;
+
+        let event =
+            EventBuilder::comment(content, &pr, None, repos.extract_relays().first().cloned())
+                .tags(verdict.tags())
+                .tag(Tag::custom(
+                    TagKind::single_letter(Alphabet::C, false),
+                    [commit],
+                ))
+                .dedup_tags()
+                .pow(options.pow.unwrap_or_default())
+                .build(user_pubk);
+
+        let event_id = event.id.expect("There is an id");
+        let write_relays = [
+            relays,
+            utils::add_write_relays(relays_list.as_ref()),
+            repos.extract_relays(),
+            client
+                .read_relays_from_users(&repos.extract_maintainers())
+                .await,
+            client.read_relays_from_user(pr.pubkey).await,
+        ]
+        .concat();
+
+        let nevent = utils::new_nevent(
+            event_id,
+            &client
+                .send_event_to(event, relays_list.as_ref(), &write_relays)
+                .await?,
+        )?;
+        println!("Review created: {nevent}");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use nostr::{
+    event::{Kind, Tag, TagKind},
+    filter::Alphabet,
+    key::Keys,
+};
+
+use super::*;
+use crate::cli::test_utils::signed_event;
+
+/// Returns the `c` tag of the PR tip
+fn tip_tag(commit: &str) -> Tag {
+    Tag::custom(TagKind::single_letter(Alphabet::C, false), [commit])
+}
+
+#[test]
+fn pr_subject_and_body_from_commits() {
+    assert_eq!(pr_subject_and_body(&[], Some("feat-x")), None);
+
+    let single = ["feat: Add x\n\nThe x body\nin two lines".to_owned()];
+    assert_eq!(
+        pr_subject_and_body(&single, Some("feat-x")),
+        Some((
+            "feat: Add x".to_owned(),
+            Some("The x body\nin two lines".to_owned())
+        ))
+    );
+    assert_eq!(
+        pr_subject_and_body(&["fix: Typo".to_owned()], None),
+        Some(("fix: Typo".to_owned(), None))
+    );
+
+    let multiple = [
+        "feat: Add x".to_owned(),
+        "docs: Document x\n\nBody".to_owned(),
+    ];
+    assert_eq!(
+        pr_subject_and_body(&multiple, Some("feat/add-x")),
+        Some((
+            "Feat add x".to_owned(),
+            Some("- feat: Add x\n- docs: Document x".to_owned())
+        ))
+    );
+    assert_eq!(
+        pr_subject_and_body(&multiple, None).map(|(subject, _)| subject),
+        Some("feat: Add x".to_owned())
+    );
+}
+
+#[test]
+fn pr_timeline_is_ordered_by_creation_time() {
+    use crate::cli::verdicts::ReviewVerdict;
+
+    let author = Keys::generate();
+    let maintainer = Keys::generate();
+    let event = |keys: &Keys, kind: Kind, content: &str, tip: Option<&str>, at: u64| {
+        signed_event(keys, kind, content, tip.map(tip_tag), at)
+    };
+
+    let [aaa, bbb, ccc] = ["a", "b", "c"].map(|c| c.repeat(40));
+    let pr = event(&author, PR_KIND, "", Some(&aaa), 100);
+    // Relays may return the updates in any order
+    let updates = [
+        event(&author, PR_UPDATE_KIND, "", Some(&ccc), 400),
+        event(&author, PR_UPDATE_KIND, "", Some(&bbb), 200),
+    ];
+    let statuses = [event(&maintainer, Kind::GitStatusApplied, "", None, 500)];
+    // Only the review verdicts are in the timeline
+    let comments = [
+        signed_event(
+            &maintainer,
+            Kind::Comment,
+            "Looks good",
+            ReviewVerdict::Approve.tags(),
+            300,
+        ),
+        event(&author, Kind::Comment, "Thanks", None, 350),
+    ];
+    let usernames = HashMap::from([
+        (author.public_key(), "Author".to_owned()),
+        (maintainer.public_key(), "Maintainer".to_owned()),
+    ]);
+
+    assert_eq!(
+        format_timeline(&pr, &updates, &statuses, &comments, &usernames),
+        format!(
+            "1970-01-01 00:01 UTC - Author opened the pull request at `{aaa}`
+1970-01-01 00:03 UTC - Author updated the tip to `{bbb}`
+1970-01-01 00:05 UTC - Maintainer approved
+1970-01-01 00:06 UTC - Author updated the tip to `{ccc}`
+1970-01-01 00:08 UTC - Maintainer changed the status to Merged/Applied"
+        )
+    );
+}
+
+#[test]
+fn pr_commit_must_be_sha1() {
+    let keys = Keys::generate();
+    let pr = |commit: &str| signed_event(&keys, PR_KIND, "", [tip_tag(commit)], 0);
+
+    let commit = "0123456789abcdef0123456789abcdef01234567";
+    assert_eq!(pr(commit).pr_commit(), Some(commit));
+    assert_eq!(pr("--upload-pack=touch /tmp/pwned").pr_commit(), None);
+    assert_eq!(pr("0123456").pr_commit(), None);
+    assert_eq!(pr("main").pr_commit(), None);
+}
+
+#[test]
+fn pr_depends_on_tags() {
+    use crate::cli::{traits::VecNostrEventExt, types::NostrEvent};
+
+    let keys = Keys::generate();
+    let base = signed_event(&keys, PR_KIND, "", [], 0);
+    let patch = signed_event(&keys, Kind::GitPatch, "", [], 0);
+    let depends_on = vec![
+        NostrEvent {
+            event_id: base.id,
+            relays:   Vec::new(),
+        },
+        NostrEvent {
+            event_id: patch.id,
+            relays:   Vec::new(),
+        },
+    ];
+
+    let pr = signed_event(&keys, PR_KIND, "", depends_on.into_depends_on_tags(), 0);
+    assert_eq!(pr.depends_on(), vec![base.id, patch.id]);
+    assert!(base.depends_on().is_empty());
+}
+
+#[test]
+fn pr_clones_are_not_taken_as_the_commit() {
+    use clap::Parser;
+    use nostr::nips::nip19::ToBech32;
+
+    use crate::cli::Cli;
+
+    let clone = "https://example.com/repo.git";
+    assert!(Cli::try_parse_from(["n34", "pr", "new", clone]).is_ok());
+    assert!(Cli::try_parse_from(["n34", "pr", "new", "--commit", "HEAD~1", clone]).is_ok());
+    assert!(Cli::try_parse_from(["n34", "pr", "new", "HEAD", clone]).is_err());
+    // The old positional tip is rejected with a hint to use `--commit`
+    let commit = "864f3018f62ab2e1265edb670d5493dafe7d2cb2";
+    let err = Cli::try_parse_from(["n34", "pr", "new", commit, clone]).unwrap_err();
+    assert!(err.to_string().contains("--commit"));
+    let err = Cli::try_parse_from(["n34", "pr", "new", "--grasp", commit]).unwrap_err();
+    assert!(err.to_string().contains("--commit"));
+
+    let pr_id = nostr::event::EventId::all_zeros().to_bech32().unwrap();
+    assert!(Cli::try_parse_from(["n34", "pr", "update", &pr_id, clone]).is_ok());
+}
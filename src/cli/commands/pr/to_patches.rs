@@ -0,0 +1,157 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use std::str::FromStr;
+
+use clap::Args;
+use nostr::event::{Event, TagKind};
+
+use crate::{
+    cli::{
+        CliOptions,
+        common_commands,
+        patch::{self, COVER_LETTER_BLURB_PLACEHOLDER, COVER_LETTER_PLACEHOLDER, GitPatch},
+        traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+        types::{EntityType, NaddrOrSet, NostrEvent, PatchPrStatus},
+        utils as cli_utils,
+    },
+    error::{N34Error, N34Result},
+    git_utils,
+    nostr_utils::{
+        NostrClient,
+        traits::{GitIssuePrMetadata, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct ToPatchesArgs {
+    /// Repository addresses
+    ///
+    /// In `naddr` format (`naddr1...`), NIP-05 format (`4rs.nl/n34` or
+    /// `_@4rs.nl/n34`), or a set name like `kernel`, separated by commas.
+    ///
+    /// If omitted, looks for a `nostr-address` file.
+    #[arg(
+        value_name = "NADDR-NIP05-OR-SET",
+        long = "repo",
+        value_delimiter = ','
+    )]
+    naddrs:         Option<Vec<NaddrOrSet>>,
+    /// Mark the series as the N-th iteration of the patch, like `git
+    /// format-patch -v N`.
+    #[arg(long, value_name = "N")]
+    reroll_count:   Option<u32>,
+    /// Original patch ID to publish the series as a revision of. Defaults to
+    /// the patch the pull request was converted from, if any.
+    #[arg(long, value_name = "EVENT-ID")]
+    original_patch: Option<NostrEvent>,
+    /// Pull request ID
+    #[arg(value_name = "EVENT-ID")]
+    pr_id:          NostrEvent,
+}
+
+impl CommandRunner for ToPatchesArgs {
+    async fn run(self, options: CliOptions) -> N34Result<()> {
+        git_utils::ensure_work_tree()?;
+
+        let naddrs = utils::check_empty_naddrs(utils::naddrs_or_file(
+            self.naddrs.clone().flat_naddrs(&options.config.sets)?,
+            &utils::nostr_address_path()?,
+        )?)?;
+        let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+        let client = NostrClient::init(&options, &relays).await;
+
+        client
+            .add_relays(&[naddrs.extract_relays(), self.pr_id.relays.clone()].concat())
+            .await;
+        let coordinates = naddrs.clone().into_coordinates();
+        let repos = client.fetch_repos(&coordinates).await?;
+        client.add_relays(&repos.extract_relays()).await;
+
+        let pr = client.fetch_pr(self.pr_id.event_id).await?;
+        let pr_update = client.fetch_pr_update(&pr).await?;
+        let tip = pr_update.as_ref().unwrap_or(&pr);
+        let commit = tip.pr_commit().ok_or(N34Error::PrWithoutCommit)?;
+        super::fetch_pr_tip(&pr, tip, commit, &repos)?;
+
+        let head = super::repo_head(&client, &coordinates, &repos).await?;
+        let base = git_utils::merge_base(&head, commit)?;
+        let patches = patches_from_pr(&pr, &base, commit, self.reroll_count)?;
+
+        let original_patch = self.original_patch.or_else(|| {
+            converted_from(&pr).map(|event_id| {
+                NostrEvent {
+                    event_id,
+                    relays: Vec::new(),
+                }
+            })
+        });
+        let root_id =
+            patch::send_patch_series(&options, naddrs, patches, original_patch, None, Vec::new())
+                .await?;
+
+        common_commands::patch_pr_status_command::<{ EntityType::PullRequest as u8 }>(
+            &options,
+            self.pr_id,
+            self.naddrs,
+            PatchPrStatus::Closed,
+            None,
+            Vec::new(),
+            Some(root_id),
+            None,
+            super::close::check_can_close,
+        )
+        .await
+    }
+}
+
+/// Generates the patches of the PR commits since `base`. A series of more
+/// than one commit gets a cover letter with the PR subject and body.
+fn patches_from_pr(
+    pr: &Event,
+    base: &str,
+    commit: &str,
+    reroll_count: Option<u32>,
+) -> N34Result<Vec<GitPatch>> {
+    let range = format!("{base}..{commit}");
+    let cover_letter = git_utils::commit_messages(&range)?.len() > 1;
+    let mbox = git_utils::format_patch(&range, cover_letter, reroll_count, Some(base))?;
+    if mbox.is_empty() {
+        return Err(N34Error::InvalidPatch(format!(
+            "No commits in the pull request range `{range}`"
+        )));
+    }
+    let mut patches = cli_utils::split_patches(mbox)?;
+
+    if cover_letter {
+        let cover_letter = patches[0]
+            .inner
+            .replace(COVER_LETTER_PLACEHOLDER, pr.extract_event_subject())
+            .replace(COVER_LETTER_BLURB_PLACEHOLDER, pr.content.trim());
+        patches[0] = GitPatch::from_str(&cover_letter).map_err(N34Error::InvalidPatch)?;
+    }
+
+    Ok(patches)
+}
+
+/// Returns the root patch the PR was converted from, referenced by its `e`
+/// tag.
+fn converted_from(pr: &Event) -> Option<nostr::event::EventId> {
+    pr.tags
+        .filter(TagKind::e())
+        .find_map(|t| t.content().and_then(|id| id.parse().ok()))
+}
@@ -18,21 +18,24 @@ use std::iter;
 
 use clap::{ArgGroup, Args};
 use nostr::{
-    event::{EventBuilder, Tag, TagKind, TagStandard, Tags},
+    event::{Event, EventBuilder, Tag, TagKind, TagStandard, Tags},
     filter::{Alphabet, Filter},
     hashes::sha1::Hash as Sha1Hash,
+    nips::nip19::Nip19Coordinate,
 };
 
 use crate::{
     cli::{
         CliOptions,
+        parsers,
         traits::{CommandRunner, OptionNaddrOrSetVecExt, RelayOrSetVecExt},
         types::{NaddrOrSet, NostrEvent},
     },
     error::{N34Error, N34Result},
+    git_utils,
     nostr_utils::{
         NostrClient,
-        traits::{NaddrsUtils, ReposUtils},
+        traits::{GitIssuePrMetadata, NaddrsUtils, ReposUtils},
         utils,
     },
 };
@@ -63,35 +66,43 @@ pub struct UpdateArgs {
     /// Push the pull request update to the repository GRASP server.
     #[arg(long, group = "clone-or-grasp")]
     grasp:       bool,
-    /// The SHA-1 hash of the commit at the tip of the PR branch.
-    ///
-    /// You can get it using `git rev-parse <branch-name>`
-    commit:      Sha1Hash,
+    /// Push the branch to the given git remote or URL before updating the
+    /// pull request, and use it as the clone URL.
+    #[arg(long, value_name = "REMOTE", group = "clone-or-grasp")]
+    push:        Option<String>,
+    /// The branch to push to with `--push`. Defaults to the current branch,
+    /// then to the pull request branch name.
+    #[arg(long, requires = "push")]
+    branch:      Option<String>,
+    /// The commit at the tip of the PR branch, a revision or a SHA-1 hash.
+    /// Defaults to `HEAD`.
+    #[arg(long, value_name = "REV")]
+    commit:      Option<String>,
     /// Repositories to clone for the pull request, separated by commas.
-    #[arg(value_delimiter = ',', group = "clone-or-grasp")]
+    #[arg(
+        value_delimiter = ',',
+        value_parser = parsers::parse_clone_url,
+        group = "clone-or-grasp"
+    )]
     clones:      Vec<nostr::Url>,
 }
 
 impl CommandRunner for UpdateArgs {
     async fn run(self, options: CliOptions) -> N34Result<()> {
+        let commit = super::resolve_tip(self.commit.as_deref())?;
         let naddrs = utils::check_empty_naddrs(utils::naddrs_or_file(
             self.naddrs.flat_naddrs(&options.config.sets)?,
             &utils::nostr_address_path()?,
         )?)?;
         let relays = options.relays.clone().flat_relays(&options.config.sets)?;
         let client = NostrClient::init(&options, &relays).await;
-        let naddrs_relays = naddrs.extract_relays();
-        client.add_relays(&naddrs_relays).await;
-        let coordinates = naddrs.into_coordinates();
-        let repos = client.fetch_repos(coordinates.as_slice()).await?;
-        let maintainers = repos.extract_maintainers();
-        let repos_relays = repos.extract_relays();
-        client.add_relays(&repos_relays).await;
-        let user_pubk = client.pubkey().await?;
-        let relays_list = client.user_relays_list(user_pubk).await?;
         client
-            .add_relays(&utils::add_read_relays(relays_list.as_ref()))
+            .add_relays(&[naddrs.extract_relays(), self.original_pr.relays].concat())
             .await;
+        let repos = client
+            .fetch_repos(&naddrs.clone().into_coordinates())
+            .await?;
+        client.add_relays(&repos.extract_relays()).await;
 
         let Some(orignal_pr) = client
             .fetch_event(
@@ -105,74 +116,127 @@ impl CommandRunner for UpdateArgs {
             return Err(N34Error::EventNotFound);
         };
 
-        // TODO: Use `CommentTarget` to mention the orignal PR
-        let mut nip22_orignal_pr = Tags::new();
-        nip22_orignal_pr.push(Tag::from_standardized_without_cell(TagStandard::Event {
-            event_id:   orignal_pr.id,
+        let clones = match &self.push {
+            Some(remote) => {
+                let branch = self
+                    .branch
+                    .or_else(git_utils::current_branch)
+                    .or_else(|| orignal_pr.pr_branch_name().map(str::to_owned));
+                Some(vec![super::push_tip(
+                    remote,
+                    &commit,
+                    branch.as_deref(),
+                    !options.non_interactive,
+                )?])
+            }
+            None if self.grasp => None,
+            None => Some(self.clones),
+        };
+
+        send_pr_update(&options, naddrs, &orignal_pr, commit, clones).await
+    }
+}
+
+/// Publishes an update of the pull request with the new tip. If `clones` is
+/// `None`, the tip is pushed to the repositories GRASP servers and the
+/// accepting server is used as the clone.
+async fn send_pr_update(
+    options: &CliOptions,
+    naddrs: Vec<Nip19Coordinate>,
+    orignal_pr: &Event,
+    commit: Sha1Hash,
+    clones: Option<Vec<nostr::Url>>,
+) -> N34Result<()> {
+    let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+    let client = NostrClient::init(options, &relays).await;
+    let naddrs_relays = naddrs.extract_relays();
+    client.add_relays(&naddrs_relays).await;
+    let coordinates = naddrs.into_coordinates();
+    let repos = client.fetch_repos(coordinates.as_slice()).await?;
+    let maintainers = repos.extract_maintainers();
+    let repos_relays = repos.extract_relays();
+    client.add_relays(&repos_relays).await;
+    let user_pubk = client.pubkey().await?;
+    let relays_list = client.user_relays_list(user_pubk).await?;
+    client
+        .add_relays(&utils::add_read_relays(relays_list.as_ref()))
+        .await;
+
+    // TODO: Use `CommentTarget` to mention the orignal PR
+    let mut nip22_orignal_pr = Tags::new();
+    nip22_orignal_pr.push(Tag::from_standardized_without_cell(TagStandard::Event {
+        event_id:   orignal_pr.id,
+        relay_url:  None,
+        marker:     None,
+        public_key: Some(orignal_pr.pubkey),
+        uppercase:  true,
+    }));
+    nip22_orignal_pr.push(Tag::from_standardized_without_cell(
+        TagStandard::PublicKey {
+            public_key: orignal_pr.pubkey,
             relay_url:  None,
-            marker:     None,
-            public_key: Some(orignal_pr.pubkey),
+            alias:      None,
             uppercase:  true,
-        }));
-        nip22_orignal_pr.push(Tag::from_standardized_without_cell(
-            TagStandard::PublicKey {
-                public_key: orignal_pr.pubkey,
-                relay_url:  None,
-                alias:      None,
-                uppercase:  true,
-            },
+        },
+    ));
+    nip22_orignal_pr.push(Tag::from_standardized_without_cell(TagStandard::Kind {
+        kind:      orignal_pr.kind,
+        uppercase: true,
+    }));
+
+    let event_builder = EventBuilder::new(super::PR_UPDATE_KIND, "")
+        .pow(options.pow.unwrap_or_default())
+        .tags(nip22_orignal_pr)
+        .tags(
+            coordinates
+                .into_iter()
+                .map(|c| Tag::coordinate(c, repos_relays.first().cloned())),
+        )
+        .tags(maintainers.iter().map(|p| Tag::public_key(*p)))
+        .tag(Tag::custom(
+            TagKind::single_letter(Alphabet::C, false),
+            iter::once(commit.to_string()),
         ));
-        nip22_orignal_pr.push(Tag::from_standardized_without_cell(TagStandard::Kind {
-            kind:      orignal_pr.kind,
-            uppercase: true,
-        }));
-
-        let mut event_builder = EventBuilder::new(super::PR_UPDATE_KIND, "")
-            .pow(options.pow.unwrap_or_default())
-            .tags(nip22_orignal_pr)
-            .tags(
-                coordinates
-                    .into_iter()
-                    .map(|c| Tag::coordinate(c, repos_relays.first().cloned())),
-            )
-            .tags(maintainers.iter().map(|p| Tag::public_key(*p)))
-            .tag(Tag::custom(
-                TagKind::single_letter(Alphabet::C, false),
-                iter::once(self.commit.to_string()),
-            ));
-
-        let event = if self.grasp {
-            utils::build_grasp_event(&repos, user_pubk, event_builder.clone())?
-        } else {
-            // Since `grasp` is false, `clones` must be provided
-            event_builder = event_builder.tag(Tag::custom(
-                TagKind::custom("clone"),
-                self.clones.iter().map(ToString::to_string),
-            ));
-
-            event_builder.build(user_pubk)
-        };
 
-        let event_id = event.id.expect("There is an id");
-
-        let write_relays = [
-            relays,
-            repos_relays,
-            naddrs_relays,
-            utils::add_write_relays(relays_list.as_ref()),
-            // Include read relays for each maintainer (if found)
-            client.read_relays_from_users(&maintainers).await,
-        ]
-        .concat();
-
-        tracing::trace!(relays = ?write_relays, "Write relays list");
-        let success = client
-            .send_event_to(event, relays_list.as_ref(), &write_relays)
-            .await?;
+    let event = match clones {
+        Some(clones) => {
+            event_builder
+                .tag(Tag::custom(
+                    TagKind::custom("clone"),
+                    clones.iter().map(ToString::to_string),
+                ))
+                .build(user_pubk)
+        }
+        None => {
+            utils::build_grasp_event(
+                &repos,
+                user_pubk,
+                event_builder,
+                &commit.to_string(),
+                !options.non_interactive,
+            )?
+        }
+    };
 
-        let nevent = utils::new_nevent(event_id, &success)?;
-        println!("PR update tip created: {nevent}");
+    let event_id = event.id.expect("There is an id");
 
-        Ok(())
-    }
+    let write_relays = [
+        relays,
+        repos_relays,
+        naddrs_relays,
+        utils::add_write_relays(relays_list.as_ref()),
+        // Include read relays for each maintainer (if found)
+        client.read_relays_from_users(&maintainers).await,
+    ]
+    .concat();
+
+    tracing::trace!(relays = ?write_relays, "Write relays list");
+    let success = client
+        .send_event_to(event, relays_list.as_ref(), &write_relays)
+        .await?;
+
+    let nevent = utils::new_nevent(event_id, &success)?;
+    println!("PR update tip created: {nevent}");
+
+    Ok(())
 }
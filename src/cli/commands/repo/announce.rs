@@ -25,7 +25,13 @@ use nostr::{
 };
 
 use crate::{
-    cli::{CliOptions, CommandRunner, NOSTR_ADDRESS_FILE, traits::RelayOrSetVecExt},
+    cli::{
+        CliOptions,
+        CommandRunner,
+        NOSTR_ADDRESS_FILE,
+        issue_templates::{self, IssueTemplate},
+        traits::RelayOrSetVecExt,
+    },
     error::N34Result,
     nostr_utils::{NostrClient, traits::NewGitRepositoryAnnouncement, utils},
 };
@@ -54,35 +60,39 @@ const PERSONAL_FORK_HASHTAG: &str = "personal-fork";
 pub struct AnnounceArgs {
     /// Unique identifier for the repository in kebab-case.
     #[arg(long = "id")]
-    repo_id:       String,
+    repo_id:         String,
     /// A name for the repository.
     #[arg(short, long)]
-    name:          Option<String>,
+    name:            Option<String>,
     /// A description for the repository.
     #[arg(short, long)]
-    description:   Option<String>,
+    description:     Option<String>,
     /// Webpage URLs for the repository (if provided by the git server).
     #[arg(short, long)]
-    web:           Vec<Url>,
+    web:             Vec<Url>,
     /// URLs for cloning the repository.
     #[arg(short, long)]
-    clone:         Vec<Url>,
+    clone:           Vec<Url>,
     /// Additional maintainers of the repository (besides yourself).
     #[arg(short, long)]
-    maintainers:   Vec<PublicKey>,
+    maintainers:     Vec<PublicKey>,
     /// Labels to categorize the repository. Can be specified multiple times.
     #[arg(short, long)]
-    label:         Vec<String>,
+    label:           Vec<String>,
     /// Skip kebab-case validation for the repository ID
     #[arg(long)]
-    force_id:      bool,
+    force_id:        bool,
     /// Indicates whether the announcement is a personal fork.
     #[arg(long)]
-    personal_fork: bool,
+    personal_fork:   bool,
     /// If set, creates a `nostr-address` file to enable automatic address
     /// discovery by n34
     #[arg(long)]
-    address_file:  bool,
+    address_file:    bool,
+    /// Publish the issue templates of the `.nostr/issue-templates/` directory
+    /// with the announcement
+    #[arg(long)]
+    issue_templates: bool,
 }
 
 impl CommandRunner for AnnounceArgs {
@@ -119,6 +129,16 @@ impl CommandRunner for AnnounceArgs {
         if self.personal_fork {
             event_builder = event_builder.tag(Tag::hashtag(PERSONAL_FORK_HASHTAG));
         }
+        if self.issue_templates {
+            let templates = issue_templates::local_templates(&issue_templates::templates_dir()?)?;
+            if templates.is_empty() {
+                tracing::warn!(
+                    "No issue templates found in `{}`",
+                    issue_templates::ISSUE_TEMPLATES_DIR
+                );
+            }
+            event_builder = event_builder.tags(templates.iter().map(IssueTemplate::to_tag));
+        }
 
         let event = event_builder.build(user_pubk);
 
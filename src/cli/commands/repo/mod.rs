@@ -21,7 +21,10 @@ mod state;
 /// `repo view` subcommand
 mod view;
 
+use std::borrow::Cow;
+
 use clap::Subcommand;
+use nostr::event::{Kind, TagKind};
 
 use self::announce::AnnounceArgs;
 use self::state::StateArgs;
@@ -29,6 +32,15 @@ use self::view::ViewArgs;
 use super::{CliOptions, CommandRunner};
 use crate::error::N34Result;
 
+/// Prefix for branch references in Git.
+pub const HEADS_REFS: &str = "refs/heads/";
+
+/// Repository state announcements kind
+pub const REPO_STATE_KIND: Kind = Kind::Custom(30618);
+
+/// `HEAD` tag kind
+pub const HEAD_TAG_KIND: TagKind = TagKind::Custom(Cow::Borrowed("HEAD"));
+
 #[derive(Subcommand, Debug)]
 pub enum RepoSubcommands {
     /// View details of a nostr git repository
@@ -14,12 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
 
-use std::borrow::Cow;
-
 use clap::Args;
-use nostr::event::{Kind, Tag, TagKind};
+use nostr::event::Tag;
 use nostr::{event::EventBuilder, hashes::sha1::Hash as Sha1Hash};
 
+use super::{HEAD_TAG_KIND, HEADS_REFS, REPO_STATE_KIND};
 use crate::nostr_utils::traits::ReposUtils;
 use crate::{
     cli::{
@@ -33,18 +32,9 @@ use crate::{
     nostr_utils::{NostrClient, traits::NaddrsUtils, utils},
 };
 
-/// Prefix for branch references in Git.
-const HEADS_REFS: &str = "refs/heads/";
-
 /// Prefix for tag references in Git.
 const TAGS_REFS: &str = "refs/tags/";
 
-/// Repository state announcements kind
-const REPO_STATE_KIND: Kind = Kind::Custom(30618);
-
-/// `HEAD` tag kind
-const HEAD_TAG_KIND: TagKind = TagKind::Custom(Cow::Borrowed("HEAD"));
-
 /// Arguments for the `repo state` command
 #[derive(Args, Debug)]
 pub struct StateArgs {
@@ -0,0 +1,173 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use nostr::{
+    event::{Event, EventId, TagKind},
+    key::PublicKey,
+    types::Timestamp,
+};
+
+use super::verdicts::ReviewVerdict;
+use crate::nostr_utils::utils;
+
+/// The number of top-level comments in a page of comments
+pub const COMMENTS_PER_PAGE: usize = 10;
+
+/// A comment with its replies
+#[derive(Debug)]
+pub struct CommentThread<'a> {
+    /// The comment
+    pub comment: &'a Event,
+    /// The replies to the comment, sorted from the oldest
+    pub replies: Vec<CommentThread<'a>>,
+}
+
+/// Returns the parent of the NIP-22 comment, its `e` tag
+fn comment_parent(comment: &Event) -> Option<EventId> {
+    comment
+        .tags
+        .find(TagKind::e())
+        .and_then(|t| t.content())
+        .and_then(|id| EventId::from_hex(id).ok())
+}
+
+/// Builds the comments tree of the root event. Comments replying to the root,
+/// or to a comment that isn't in `comments`, are top-level comments. Threads
+/// are sorted from the oldest.
+pub fn build_threads(root_id: EventId, comments: &[Event]) -> Vec<CommentThread<'_>> {
+    let ids = comments.iter().map(|c| c.id).collect::<Vec<_>>();
+    let mut children: HashMap<Option<EventId>, Vec<&Event>> = HashMap::new();
+    for comment in comments {
+        let parent = comment_parent(comment)
+            .filter(|parent| *parent != root_id && *parent != comment.id && ids.contains(parent));
+        children.entry(parent).or_default().push(comment);
+    }
+    for replies in children.values_mut() {
+        replies.sort_by_key(|c| c.created_at);
+    }
+
+    fn build<'a>(
+        parent: Option<EventId>,
+        children: &HashMap<Option<EventId>, Vec<&'a Event>>,
+    ) -> Vec<CommentThread<'a>> {
+        children
+            .get(&parent)
+            .into_iter()
+            .flatten()
+            .map(|comment| {
+                CommentThread {
+                    comment,
+                    replies: build(Some(comment.id), children),
+                }
+            })
+            .collect()
+    }
+
+    build(None, &children)
+}
+
+/// Returns the threads of the given page, starting from 1, and the number of
+/// pages. Each page contains [`COMMENTS_PER_PAGE`] top-level comments with
+/// their replies.
+pub fn paginate<'a, 'b>(
+    threads: &'b [CommentThread<'a>],
+    page: usize,
+) -> (&'b [CommentThread<'a>], usize) {
+    let pages = threads.len().div_ceil(COMMENTS_PER_PAGE).max(1);
+    let start = page.saturating_sub(1).saturating_mul(COMMENTS_PER_PAGE);
+    let end = start.saturating_add(COMMENTS_PER_PAGE).min(threads.len());
+    (threads.get(start..end).unwrap_or_default(), pages)
+}
+
+/// Formats the threads with their replies indented under them. Each comment
+/// starts with its author name and its date relative to `now`. Authors without
+/// a name in `usernames` are shown as `Unknown`.
+pub fn format_threads(
+    threads: &[CommentThread<'_>],
+    usernames: &HashMap<PublicKey, String>,
+    now: Timestamp,
+) -> String {
+    fn format_thread(
+        thread: &CommentThread<'_>,
+        depth: usize,
+        usernames: &HashMap<PublicKey, String>,
+        now: Timestamp,
+        lines: &mut Vec<String>,
+    ) {
+        let indent = "  ".repeat(depth);
+        let comment = thread.comment;
+        let action = ReviewVerdict::from_event(comment).map_or("commented", |v| v.past_tense());
+        lines.push(format!(
+            "{indent}{} {action} {}:",
+            usernames
+                .get(&comment.pubkey)
+                .map(String::as_str)
+                .unwrap_or("Unknown"),
+            utils::format_relative_date(comment.created_at, now)
+        ));
+        lines.extend(
+            utils::smart_wrap(&comment.content, 80)
+                .lines()
+                .map(|line| format!("{indent}  {line}")),
+        );
+        for reply in &thread.replies {
+            format_thread(reply, depth + 1, usernames, now, lines);
+        }
+    }
+
+    let mut lines = Vec::new();
+    for thread in threads {
+        format_thread(thread, 0, usernames, now, &mut lines);
+    }
+    lines.join("\n")
+}
+
+/// Formats the given page of the comments threads of the root event, with a
+/// `Comments:` header after an empty line. Returns an empty string if there
+/// are no comments.
+pub fn format_comments(
+    root_id: EventId,
+    comments: &[Event],
+    usernames: &HashMap<PublicKey, String>,
+    page: usize,
+) -> String {
+    let threads = build_threads(root_id, comments);
+    if threads.is_empty() {
+        return String::new();
+    }
+    let (page_threads, pages) = paginate(&threads, page);
+    let footer = match pages {
+        1 => String::new(),
+        _ if page < pages => {
+            format!(
+                "\n\nPage {page} of {pages}, use `--comments-page {}` for more",
+                page + 1
+            )
+        }
+        _ => format!("\n\nPage {page} of {pages}"),
+    };
+
+    format!(
+        "\n\nComments ({}):\n{}{footer}",
+        comments.len(),
+        format_threads(page_threads, usernames, Timestamp::now())
+    )
+}
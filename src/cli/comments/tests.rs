@@ -0,0 +1,73 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use nostr::{
+    event::{Kind, Tag},
+    key::Keys,
+};
+
+use super::*;
+use crate::cli::{pr::PR_KIND, test_utils::signed_event};
+
+#[test]
+fn comments_threads() {
+    let author = Keys::generate();
+    let maintainer = Keys::generate();
+    let pr = signed_event(&author, PR_KIND, "", [], 0);
+    let comment = |keys: &Keys, content: &str, parent: &Event, at: u64| {
+        signed_event(keys, Kind::Comment, content, [Tag::event(parent.id)], at)
+    };
+
+    let first = comment(&maintainer, "First", &pr, 100);
+    let reply = comment(&author, "Reply", &first, 200);
+    let nested = comment(&maintainer, "Nested\nreply", &reply, 300);
+    let second = comment(&author, "Second", &pr, 150);
+    // Relays may return the comments in any order
+    let events = [nested, second, reply, first];
+    let usernames = HashMap::from([
+        (author.public_key(), "Author".to_owned()),
+        (maintainer.public_key(), "Maintainer".to_owned()),
+    ]);
+
+    let threads = build_threads(pr.id, &events);
+    assert_eq!(
+        format_threads(&threads, &usernames, Timestamp::from(3700)),
+        "Maintainer commented 1 hour ago:
+  First
+  Author commented 58 minutes ago:
+    Reply
+    Maintainer commented 56 minutes ago:
+      Nested
+      reply
+Author commented 59 minutes ago:
+  Second"
+    );
+}
+
+#[test]
+fn comments_pages() {
+    let author = Keys::generate();
+    let pr = signed_event(&author, PR_KIND, "", [], 0);
+    let many = (0..COMMENTS_PER_PAGE as u64 + 1)
+        .map(|at| signed_event(&author, Kind::Comment, "Comment", [Tag::event(pr.id)], at))
+        .collect::<Vec<_>>();
+
+    let threads = build_threads(pr.id, &many);
+    assert_eq!(paginate(&threads, 1).0.len(), COMMENTS_PER_PAGE);
+    assert_eq!(paginate(&threads, 2).0.len(), 1);
+    assert_eq!(paginate(&threads, 2).1, 2);
+    assert!(paginate(&threads, 3).0.is_empty());
+}
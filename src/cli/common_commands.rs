@@ -14,25 +14,33 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
 
-use std::{iter, str::FromStr, sync::Arc};
+use std::{cmp::Reverse, collections::HashSet, iter, num::NonZeroUsize, str::FromStr, sync::Arc};
 
+use clap::Args;
 use either::Either;
 use futures::future;
 use nostr::{
-    event::{Event, EventBuilder, EventId, Kind, Tag, TagKind, TagStandard},
-    filter::{Alphabet, Filter, SingleLetterTag},
+    event::{Event, EventBuilder, EventId, Kind, Tag, TagKind},
+    filter::Filter,
     hashes::sha1::Hash as Sha1Hash,
+    key::PublicKey,
     nips::{nip10::Marker, nip19::ToBech32},
-    types::RelayUrl,
+    types::{RelayUrl, Timestamp},
 };
 
 use super::{
+    comments,
+    dependencies,
     issue::IssueStatus,
-    types::{NaddrOrSet, NostrEvent},
+    labels::{self, IssuePrMetadata},
+    parsers,
+    types::{ListSort, NaddrOrSet, NostrEvent, UserOrMe},
+    verdicts,
 };
 use crate::{
     cli::{CliOptions, patch::GitPatch, types::PatchPrStatus},
     error::{N34Error, N34Result},
+    git_utils,
     nostr_utils::traits::{GitIssuePrMetadata, GitPatchUtils, ReposUtils},
 };
 use crate::{
@@ -126,14 +134,19 @@ pub async fn issue_status_command(
 }
 
 /// Updates the patch/pr's status to `new_status` after validating it with
-/// `check_fn`. The `ENTITY_TYPE` can only be a pull request or a patch
+/// `check_fn`. The `ENTITY_TYPE` can only be a pull request or a patch. If
+/// it's `replaced_by` another patch or PR, the status links to it. The `note`
+/// is added to the status content.
+#[allow(clippy::too_many_arguments)]
 pub async fn patch_pr_status_command<const ENTITY_TYPE: u8>(
-    options: CliOptions,
+    options: &CliOptions,
     patch_pr_id: NostrEvent,
     naddrs: Option<Vec<NaddrOrSet>>,
     new_status: PatchPrStatus,
     merge_or_applied_commits: Option<Either<Sha1Hash, Vec<Sha1Hash>>>,
     merge_or_applied_patches: Vec<EventId>,
+    replaced_by: Option<EventId>,
+    note: Option<String>,
     check_fn: impl FnOnce(&PatchPrStatus) -> N34Result<()>,
 ) -> N34Result<()> {
     EntityType::is_pr_or_patch::<ENTITY_TYPE>();
@@ -144,7 +157,7 @@ pub async fn patch_pr_status_command<const ENTITY_TYPE: u8>(
         &utils::nostr_address_path()?,
     )?;
     let relays = options.relays.clone().flat_relays(&options.config.sets)?;
-    let client = NostrClient::init(&options, &relays).await;
+    let client = NostrClient::init(options, &relays).await;
     let user_pubk = client.pubkey().await?;
     client
         .add_relays(&[naddrs.extract_relays(), patch_pr_id.relays].concat())
@@ -188,8 +201,31 @@ pub async fn patch_pr_status_command<const ENTITY_TYPE: u8>(
     };
 
     check_fn(&current_status)?;
+    if new_status.is_merged_or_applied() {
+        dependencies::check_merged_dependencies(
+            &client,
+            &event,
+            &maintainers,
+            !options.non_interactive,
+        )
+        .await?;
+    }
 
-    let mut status_builder = EventBuilder::new(new_status.kind(), "")
+    let replaced_by_line = match replaced_by {
+        Some(replacement) => {
+            Some(format!(
+                "Replaced by nostr:{}",
+                utils::new_nevent(replacement, relay_hint.as_slice())?
+            ))
+        }
+        None => None,
+    };
+    let content = replaced_by_line
+        .into_iter()
+        .chain(note)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut status_builder = EventBuilder::new(new_status.kind(), content)
         .pow(options.pow.unwrap_or_default())
         .tag(utils::event_reply_tag(
             &root_patch_or_pr,
@@ -203,6 +239,20 @@ pub async fn patch_pr_status_command<const ENTITY_TYPE: u8>(
                 .map(|c| Tag::coordinate(c, relay_hint.clone())),
         );
 
+    if let Some(replacement) = replaced_by {
+        status_builder = status_builder.tag(Tag::custom(
+            TagKind::q(),
+            [
+                replacement.to_hex(),
+                relay_hint
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                user_pubk.to_hex(),
+            ],
+        ));
+    }
+
     if new_status.is_merged_or_applied() {
         if let Some(merge_commit) = merge_or_applied_commits
             .as_ref()
@@ -264,16 +314,162 @@ pub async fn patch_pr_status_command<const ENTITY_TYPE: u8>(
     Ok(())
 }
 
+/// Filters, order and page of the listed issues, patches and PRs
+#[derive(Args, Debug)]
+pub struct ListFilters {
+    /// Only list the ones with these statuses, separated by commas. One of
+    /// `open`, `draft`, `closed`, `merged`, `applied` or `resolved`
+    #[arg(long, value_name = "STATUS", value_delimiter = ',')]
+    pub status: Vec<PatchPrStatus>,
+    /// Only list the ones created by this user, a public key or `me` for the
+    /// signer
+    #[arg(long, value_name = "NPUB-OR-ME")]
+    pub author: Option<UserOrMe>,
+    /// Only list the ones with all of these labels, separated by commas
+    #[arg(long, value_delimiter = ',')]
+    pub label:  Vec<String>,
+    /// Only list the ones created after this date, a `YYYY-MM-DD` date or a
+    /// relative date like `2w`
+    #[arg(long, value_name = "DATE", value_parser = parsers::parse_date)]
+    pub since:  Option<Timestamp>,
+    /// Only list the ones created before this date, a `YYYY-MM-DD` date or a
+    /// relative date like `2w`
+    #[arg(long, value_name = "DATE", value_parser = parsers::parse_date)]
+    pub until:  Option<Timestamp>,
+    /// The page order, `created`, `updated` or `status`. The pages are
+    /// selected by the creation time
+    #[arg(long, value_name = "ORDER", default_value = "status")]
+    pub sort:   ListSort,
+    /// The page to list, from the newest. Each page has `--limit` items
+    #[arg(long, default_value = "1")]
+    pub page:   NonZeroUsize,
+}
+
+impl Default for ListFilters {
+    fn default() -> Self {
+        Self {
+            status: Vec::new(),
+            author: None,
+            label:  Vec::new(),
+            since:  None,
+            until:  None,
+            sort:   ListSort::default(),
+            page:   NonZeroUsize::MIN,
+        }
+    }
+}
+
 /// Fetches and displays pull requests, patches, and issues for specified
-/// repositories. The `limit` parameter sets the maximum number of items to
-/// retrieve.
+/// repositories. The `limit` parameter sets the maximum number of items in a
+/// page, the `filters` select the page and the items to display, and the
+/// `assignee` only keeps the issues and PRs assigned to them.
 ///
 /// The `ENTITY_TYPE` const is `[EntityType]` enum as u8.
 pub async fn list_pr_patches_and_issues<const ENTITY_TYPE: u8>(
     options: CliOptions,
     naddrs: Option<Vec<NaddrOrSet>>,
     limit: usize,
+    filters: ListFilters,
+    assignee: Option<UserOrMe>,
 ) -> N34Result<()> {
+    let entity_type = EntityType::from_u8::<ENTITY_TYPE>();
+    if matches!(assignee, Some(UserOrMe::Me)) || matches!(filters.author, Some(UserOrMe::Me)) {
+        options.ensure_signer()?;
+    }
+    let (client, events, has_more) =
+        fetch_with_client::<ENTITY_TYPE>(&options, naddrs, limit, &filters, assignee).await?;
+
+    let lines = future::join_all(events.into_iter().map(|(event, (status, _), metadata)| {
+        let client = Arc::clone(&client);
+        async move {
+            let assignees = metadata
+                .as_ref()
+                .map(|m| m.assignees.clone())
+                .unwrap_or_default();
+            let mut line = format_entity::<ENTITY_TYPE>(&event, status, metadata);
+            if !assignees.is_empty() {
+                let usernames = client.get_usernames(assignees.iter().copied()).await;
+                line.push_str(&format!(
+                    "Assignees: {}\n",
+                    assignees
+                        .iter()
+                        .map(|user| usernames[user].as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            if entity_type.is_pr()
+                && let Ok(verdicts) = verdicts::fetch_pr_verdicts(&client, &event).await
+                && let Some(summary) = verdicts::format_verdicts_summary(&verdicts)
+            {
+                line.push_str(&format!("Reviews: {summary}\n"));
+            }
+            line
+        }
+    }))
+    .await;
+
+    let max_width = lines
+        .iter()
+        .map(|s| s.split_once('\n').map_or(85, |(l, _)| l.chars().count()))
+        .max()
+        .unwrap_or(85)
+        .max(67); // length of the event id
+
+    println!("{}", lines.join(&format!("{}\n", "-".repeat(max_width))));
+    if has_more {
+        println!(
+            "Page {page}, use `--page {}` for the next page",
+            filters.page.get() + 1,
+            page = filters.page
+        );
+    }
+
+    Ok(())
+}
+
+/// Fetches pull requests, patches, or issues of the specified repositories with
+/// their status name and kind, sorted by the status kind. The `limit` parameter
+/// sets the maximum number of items to retrieve.
+///
+/// The `ENTITY_TYPE` const is `[EntityType]` enum as u8.
+pub async fn fetch_pr_patches_and_issues<const ENTITY_TYPE: u8>(
+    options: &CliOptions,
+    naddrs: Option<Vec<NaddrOrSet>>,
+    limit: usize,
+) -> N34Result<Vec<(Event, (&'static str, u16))>> {
+    fetch_with_client::<ENTITY_TYPE>(options, naddrs, limit, &ListFilters::default(), None)
+        .await
+        .map(|(_, events, _)| {
+            events
+                .into_iter()
+                .map(|(event, status, _)| (event, status))
+                .collect()
+        })
+}
+
+/// A listed pull request, patch or issue with its status name and kind, and
+/// its metadata if it's not a patch.
+type ListedEvent = (Event, (&'static str, u16), Option<IssuePrMetadata>);
+
+/// Same as [`fetch_pr_patches_and_issues`], but fetches the page of the
+/// `filters` and sorts it by their order. Also returns the client used to
+/// fetch them, with the repositories relays added, the metadata of the issues
+/// and PRs, and whether there are more pages.
+///
+/// The events are fetched from the newest, in batches of `limit` events, each
+/// batch `until` the creation time of the oldest event of the previous one,
+/// skipping the events already seen in it. The batches are fetched until the
+/// page is full of the events that match the filters and the `assignee`, or
+/// there are no more events. The page is selected by the creation time, then
+/// sorted by the filters order.
+async fn fetch_with_client<const ENTITY_TYPE: u8>(
+    options: &CliOptions,
+    naddrs: Option<Vec<NaddrOrSet>>,
+    limit: usize,
+    filters: &ListFilters,
+    assignee: Option<UserOrMe>,
+) -> N34Result<(Arc<NostrClient>, Vec<ListedEvent>, bool)> {
     let naddrs = utils::check_empty_naddrs(utils::naddrs_or_file(
         naddrs.flat_naddrs(&options.config.sets)?,
         &utils::nostr_address_path()?,
@@ -281,12 +477,14 @@ pub async fn list_pr_patches_and_issues<const ENTITY_TYPE: u8>(
 
     let entity_type = EntityType::from_u8::<ENTITY_TYPE>();
     let relays = options.relays.clone().flat_relays(&options.config.sets)?;
-    let client = NostrClient::init(&options, &relays).await;
+    let client = NostrClient::init(options, &relays).await;
     client.add_relays(&naddrs.extract_relays()).await;
 
     let coordinates = naddrs.clone().into_coordinates();
     let repos = client.fetch_repos(&coordinates).await?;
-    let authorized_pubkeys = [naddrs.extract_owners(), repos.extract_maintainers()].concat();
+    let owners = naddrs.extract_owners();
+    let maintainers = repos.extract_maintainers();
+    let authorized_pubkeys = [owners.as_slice(), &maintainers].concat();
     client.add_relays(&repos.extract_relays()).await;
     // This helps discover issues and their status.
     client
@@ -301,78 +499,167 @@ pub async fn list_pr_patches_and_issues<const ENTITY_TYPE: u8>(
     if entity_type.is_patch() {
         filter = filter.hashtag("root");
     }
+    if let Some(since) = filters.since {
+        filter = filter.since(since);
+    }
+    match filters.author {
+        Some(UserOrMe::Me) => filter = filter.author(client.pubkey().await?),
+        Some(UserOrMe::User(author)) => filter = filter.author(author),
+        None => {}
+    }
+    let assignee = match assignee {
+        Some(UserOrMe::Me) => Some(client.pubkey().await?),
+        Some(UserOrMe::User(user)) => Some(user),
+        None => None,
+    };
+    let required_labels = labels::normalize_labels(filters.label.clone());
 
-    let arc_client = Arc::new(client);
-    // Events are sorted by kind in ascending order:
-    // 1630 (Open), 1631 (Resolved/Applied), 1632 (Closed), 1633 (Draft)
-    let events = utils::sort_by_key(
-        future::join_all(
-            arc_client
-                .fetch_events(filter)
-                .await?
-                .take(limit)
-                .map(|event| {
-                    let c = arc_client.clone();
-                    let keys = authorized_pubkeys.clone();
-                    async move {
-                        let status = match entity_type {
-                            EntityType::PullRequest => {
-                                c.fetch_pr_status(
-                                    event.id,
-                                    [keys.as_slice(), &[event.pubkey]].concat(),
-                                )
+    let client = Arc::new(client);
+    let skip = (filters.page.get() - 1) * limit;
+    let mut until = filters.until;
+    let mut seen = HashSet::new();
+    let mut matches = Vec::new();
+    // One more match than the page tells whether there are more pages.
+    while matches.len() <= skip + limit {
+        let batch_filter = match until {
+            Some(until) => filter.clone().until(until),
+            None => filter.clone(),
+        };
+        let batch = client
+            .fetch_events(batch_filter)
+            .await?
+            .take(limit)
+            .collect::<Vec<_>>();
+        let exhausted = batch.len() < limit;
+        let oldest = batch.iter().map(|e| e.created_at).min();
+        // The events created in the same second as the oldest one are fetched
+        // again by the next batch, so they are skipped.
+        let batch = batch
+            .into_iter()
+            .filter(|e| seen.insert(e.id))
+            .collect::<Vec<_>>();
+        until = match oldest {
+            // A full batch of seen events, all created in the same second
+            Some(oldest) if batch.is_empty() => {
+                Some(Timestamp::from(oldest.as_u64().saturating_sub(1)))
+            }
+            oldest => oldest,
+        };
+
+        matches.extend(
+            future::join_all(batch.into_iter().map(|event| {
+                let c = Arc::clone(&client);
+                let (owners, maintainers) = (&owners, &maintainers);
+                let authorized_pubkeys = &authorized_pubkeys;
+                let required_labels = &required_labels;
+                async move {
+                    let keys = [authorized_pubkeys.as_slice(), &[event.pubkey]].concat();
+                    let status = match entity_type {
+                        EntityType::PullRequest => {
+                            c.fetch_pr_status(event.id, keys)
                                 .await
                                 .map(|s| (s.as_str(), s.kind().as_u16()))?
-                            }
-                            EntityType::Patch => {
-                                let (root, root_revision) = get_patch_root_revision(&event)?;
-                                c.fetch_patch_status(
-                                    root,
-                                    root_revision,
-                                    [keys.as_slice(), &[event.pubkey]].concat(),
-                                )
+                        }
+                        EntityType::Patch => {
+                            let (root, root_revision) = get_patch_root_revision(&event)?;
+                            c.fetch_patch_status(root, root_revision, keys)
                                 .await
                                 .map(|s| (s.as_str(), s.kind().as_u16()))?
-                            }
-                            EntityType::Issue => {
-                                c.fetch_issue_status(
-                                    event.id,
-                                    [keys.as_slice(), &[event.pubkey]].concat(),
-                                )
+                        }
+                        EntityType::Issue => {
+                            c.fetch_issue_status(event.id, keys)
                                 .await
                                 .map(|s| (s.as_str(), s.kind().as_u16()))?
-                            }
-                        };
+                        }
+                    };
+                    if !filters.status.is_empty()
+                        && !filters.status.iter().any(|s| s.kind().as_u16() == status.1)
+                    {
+                        return Ok(None);
+                    }
 
-                        N34Result::Ok((event, status))
+                    let metadata = if entity_type.is_patch() {
+                        None
+                    } else {
+                        Some(
+                            labels::fetch_metadata(&c, &event, owners, maintainers)
+                                .await
+                                .unwrap_or_else(|_| {
+                                    IssuePrMetadata::from_events(&event, &[], &[], &[])
+                                }),
+                        )
+                    };
+                    let (event_labels, assignees) = match &metadata {
+                        Some(metadata) => (metadata.labels.clone(), metadata.assignees.clone()),
+                        None => {
+                            (
+                                IssuePrMetadata::from_events(&event, &[], &[], &[]).labels,
+                                Vec::new(),
+                            )
+                        }
+                    };
+                    if assignee.is_some_and(|user| !assignees.contains(&user))
+                        || required_labels
+                            .iter()
+                            .any(|label| !event_labels.contains(label))
+                    {
+                        return Ok(None);
                     }
-                }),
-        )
-        .await
-        .into_iter()
-        .filter_map(|r| r.ok()),
-        |(_, (_, k))| *k,
-    );
 
-    let lines = events
-        .map(|(event, (status, _))| format_entity::<ENTITY_TYPE>(&event, status))
-        .collect::<Vec<String>>();
+                    N34Result::Ok(Some((event, status, metadata)))
+                }
+            }))
+            .await
+            .into_iter()
+            .filter_map(|r| r.ok().flatten()),
+        );
 
-    let max_width = lines
-        .iter()
-        .map(|s| s.split_once('\n').map_or(85, |(l, _)| l.chars().count()))
-        .max()
-        .unwrap_or(85)
-        .max(67); // length of the event id
+        if exhausted {
+            break;
+        }
+    }
+    let has_more = matches.len() > skip + limit;
 
-    println!("{}", lines.join(&format!("{}\n", "-".repeat(max_width))));
+    let page = matches
+        .into_iter()
+        .skip(skip)
+        .take(limit)
+        .collect::<Vec<_>>();
+    let activities = if matches!(filters.sort, ListSort::Updated) {
+        let events = page.iter().map(|(event, ..)| event).collect::<Vec<_>>();
+        client
+            .fetch_last_activities(&events)
+            .await
+            .unwrap_or_default()
+    } else {
+        Default::default()
+    };
+    let events = page.into_iter().map(|(event, status, metadata)| {
+        let updated_at = activities
+            .get(&event.id)
+            .copied()
+            .unwrap_or(event.created_at);
+        (event, status, metadata, updated_at)
+    });
+
+    // Statuses are sorted by kind in ascending order:
+    // 1630 (Open), 1631 (Resolved/Applied), 1632 (Closed), 1633 (Draft)
+    let events = utils::sort_by_key(events, |(event, (_, kind), _, updated_at)| {
+        match filters.sort {
+            ListSort::Created => (0, Reverse(event.created_at)),
+            ListSort::Updated => (0, Reverse(*updated_at)),
+            ListSort::Status => (*kind, Reverse(event.created_at)),
+        }
+    })
+    .map(|(event, status, metadata, _)| (event, status, metadata))
+    .collect();
 
-    Ok(())
+    Ok((client, events, has_more))
 }
 
 /// Returns a tuple of (root_id, patch_id) if this is a valid root or revision
 /// patch.
-fn get_patch_root_revision(patch_event: &Event) -> N34Result<(EventId, Option<EventId>)> {
+pub fn get_patch_root_revision(patch_event: &Event) -> N34Result<(EventId, Option<EventId>)> {
     if patch_event.is_revision_patch() {
         Ok((
             patch_event.root_patch_from_revision()?,
@@ -385,10 +672,51 @@ fn get_patch_root_revision(patch_event: &Event) -> N34Result<(EventId, Option<Ev
     }
 }
 
+/// Verifies the merge commit of a patch or PR in the local repository and
+/// returns it. It must be reachable from the default branch and include the
+/// `tip`. If it's `None`, the `tip` itself is used if it was fast-forwarded or
+/// applied onto `HEAD`, otherwise the first merge on `HEAD` that merged it.
+pub fn verify_merge_commit(merge_commit: Option<Sha1Hash>, tip: &str) -> N34Result<Sha1Hash> {
+    let merge_commit = match merge_commit {
+        Some(commit) => {
+            git_utils::rev_parse_commit(&commit.to_string())
+                .map_err(|_| N34Error::MergeCommitNotFound(commit.to_string()))?
+        }
+        None => {
+            let commit = git_utils::merge_commit_including(tip)?
+                .ok_or_else(|| N34Error::NoMergeCommit(tip.to_owned()))?;
+            tracing::info!("Found the merge commit `{commit}`");
+            commit
+        }
+    };
+
+    let default_branch = git_utils::default_branch()?;
+    if !git_utils::is_ancestor(&merge_commit, &default_branch)? {
+        return Err(N34Error::MergeCommitNotInDefaultBranch(
+            merge_commit,
+            default_branch,
+        ));
+    }
+    if !git_utils::is_ancestor(tip, &merge_commit)? {
+        return Err(N34Error::MergeCommitWithoutTip(
+            merge_commit,
+            tip.to_owned(),
+        ));
+    }
+
+    Sha1Hash::from_str(&merge_commit)
+        .map_err(|err| N34Error::GitCommand("rev-parse".to_owned(), err.to_string()))
+}
+
 /// Formats patch, issue or PR. For patches, extracts the
 /// subject line from the Git patch format. For issues and PRs, combines the
-/// subject with labels. The output includes status and formatted ID.
-fn format_entity<const ENTITY_TYPE: u8>(event: &Event, status: &str) -> String {
+/// subject with labels, taken from the `metadata` if given. The output includes
+/// status and formatted ID.
+fn format_entity<const ENTITY_TYPE: u8>(
+    event: &Event,
+    status: &str,
+    metadata: Option<IssuePrMetadata>,
+) -> String {
     let entity_type = EntityType::from_u8::<ENTITY_TYPE>();
 
     let subject = match entity_type {
@@ -408,13 +736,14 @@ fn format_entity<const ENTITY_TYPE: u8>(event: &Event, status: &str) -> String {
         }
         _ => {
             // Issues and PRs
-            let labels = event.extract_event_labels();
-            let subject = event.extract_event_subject();
+            let metadata =
+                metadata.unwrap_or_else(|| IssuePrMetadata::from_events(event, &[], &[], &[]));
+            let labels = metadata.format_labels();
 
             if labels.is_empty() {
-                subject.to_owned()
+                metadata.subject
             } else {
-                format!(r#""{subject}" {labels}"#)
+                format!(r#""{}" {labels}"#, metadata.subject)
             }
         }
     };
@@ -463,10 +792,13 @@ async fn build_patches_quote(
     .await
 }
 
+/// Prints the issue or PR with the given page of its comments threads, `None`
+/// to hide the comments.
 pub async fn view_pr_issue<const IS_PR: bool>(
     options: CliOptions,
     naddrs: Option<Vec<NaddrOrSet>>,
     event_id: NostrEvent,
+    comments_page: Option<usize>,
 ) -> N34Result<()> {
     let naddrs = utils::naddrs_or_file(
         naddrs.flat_naddrs(&options.config.sets)?,
@@ -477,7 +809,9 @@ pub async fn view_pr_issue<const IS_PR: bool>(
 
     client.add_relays(&naddrs.extract_relays()).await;
     client.add_relays(&event_id.relays).await;
+    let owners = naddrs.extract_owners();
     let repos = client.fetch_repos(&naddrs.into_coordinates()).await?;
+    let maintainers = repos.extract_maintainers();
     client.add_relays(&repos.extract_relays()).await;
 
     let event = client
@@ -500,77 +834,146 @@ pub async fn view_pr_issue<const IS_PR: bool>(
                 }
             },
         )?;
-    let authorized_pubkeys = [repos.extract_maintainers().as_slice(), &[event.pubkey]].concat();
-    let status = if IS_PR {
-        client
-            .fetch_pr_status(event.id, authorized_pubkeys)
-            .await?
-            .to_string()
-    } else {
-        client
-            .fetch_issue_status(event.id, authorized_pubkeys)
-            .await?
-            .to_string()
-    };
-
-    let event_subject = utils::smart_wrap(event.extract_event_subject(), 70);
-    let event_author = client.get_username(event.pubkey).await;
-    let mut event_labels = utils::smart_wrap(&event.extract_event_labels(), 70);
+    let authorized_pubkeys = [owners.as_slice(), &maintainers, &[event.pubkey]].concat();
+    let metadata = labels::fetch_metadata(&client, &event, &owners, &maintainers).await?;
 
-    if event_labels.is_empty() {
-        event_labels = "\n".to_owned();
-    } else {
-        event_labels = format!("{event_labels}\n\n")
+    if IS_PR {
+        return view_pr(&client, event, metadata, authorized_pubkeys, comments_page).await;
     }
 
-    let pr_data = if IS_PR {
-        // Check if there is an update
-        let pr_update = client
-            .fetch_event(
-                Filter::new()
-                    .kind(crate::cli::pr::PR_UPDATE_KIND)
-                    .custom_tag(SingleLetterTag::uppercase(Alphabet::E), event.id)
-                    .author(event.pubkey),
-            )
-            .await?;
+    let status = client
+        .fetch_issue_status(event.id, authorized_pubkeys)
+        .await?
+        .to_string();
+    let event_subject = utils::smart_wrap(&metadata.subject, 70);
+    let event_author = client.get_username(event.pubkey).await;
+    let event_labels = format_labels(&metadata);
+    // The assignees follow the issue content
+    let assignees = match format_assignees(&client, &metadata).await {
+        assignees if assignees.is_empty() => assignees,
+        assignees => format!("\n\n{}", assignees.trim_end()),
+    };
+    let comments = match comments_page {
+        Some(page) => {
+            let comments = client.fetch_comments(event.id).await?;
+            let usernames = client
+                .get_usernames(comments.iter().map(|c| c.pubkey))
+                .await;
+            comments::format_comments(event.id, &comments, &usernames, page)
+        }
+        None => String::new(),
+    };
 
-        let commit = pr_update
-            .as_ref()
-            .unwrap_or(&event)
-            .tags
-            .find(TagKind::single_letter(Alphabet::C, false))
-            .and_then(|t| t.content())
-            .unwrap_or("N/A");
-        let branch = event
-            .tags
-            .find(TagKind::custom("branch-name"))
-            .and_then(|t| t.content())
-            .unwrap_or("N/A");
-        let clones = pr_update
-            .as_ref()
-            .unwrap_or(&event)
-            .tags
-            .iter()
-            .filter_map(|t| t.as_standardized())
-            .find_map(|t| {
-                match t {
-                    TagStandard::GitClone(urls) if !urls.is_empty() => Some(urls.clone()),
-                    _ => None,
-                }
-            })
-            .unwrap_or_default();
+    println!(
+        "({status}) {event_subject} - [by {event_author}]\n{event_labels}{}{assignees}{comments}",
+        utils::smart_wrap(&event.content, 80)
+    );
+    Ok(())
+}
 
-        format!(
-            "\nCommit: {commit}\nBranch: {branch}\nClone:\n{}",
-            utils::format_iter(clones)
+/// Prints the PR with its current tip and its timeline. The current tip is the
+/// latest update of the PR author, or the PR itself if it has no updates.
+async fn view_pr(
+    client: &NostrClient,
+    pr: Event,
+    metadata: IssuePrMetadata,
+    authorized_pubkeys: Vec<PublicKey>,
+    comments_page: Option<usize>,
+) -> N34Result<()> {
+    let updates = client.fetch_pr_updates(&pr).await?;
+    let statuses = client
+        .fetch_pr_status_events(pr.id, authorized_pubkeys.clone())
+        .await?;
+    let comments = client.fetch_comments(pr.id).await?;
+    let usernames = client
+        .get_usernames(
+            iter::once(&pr)
+                .chain(&statuses)
+                .chain(&comments)
+                .map(|e| e.pubkey),
         )
+        .await;
+
+    let status = statuses
+        .last()
+        .and_then(|status| PatchPrStatus::try_from(status.kind).ok())
+        .unwrap_or(PatchPrStatus::Open);
+    let tip = updates.last().unwrap_or(&pr);
+    let dependencies = dependencies::fetch_dependencies(client, &pr, &authorized_pubkeys).await?;
+    let reviews = match tip.pr_commit() {
+        Some(commit) => {
+            verdicts::current_verdicts(
+                &comments
+                    .iter()
+                    .filter(|c| verdicts::reviewed_commit(c) == Some(commit))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            )
+        }
+        None => Vec::new(),
+    };
+    let reviews = if reviews.is_empty() {
+        String::new()
     } else {
+        format!(
+            "Reviews:\n{}\n\n",
+            utils::format_iter(verdicts::format_verdicts(&reviews, &usernames))
+        )
+    };
+    let assignees = format_assignees(client, &metadata).await;
+    let depends_on = if dependencies.is_empty() {
         String::new()
+    } else {
+        format!(
+            "Depends on:\n{}\n\n",
+            utils::format_iter(
+                dependencies
+                    .iter()
+                    .map(|(e, s)| dependencies::format_dependency(e, s))
+            )
+        )
     };
 
     println!(
-        "({status}) {event_subject} - [by {event_author}]\n{event_labels}{}{pr_data}",
-        utils::smart_wrap(&event.content, 80)
+        "({status}) {} - [by {}]\n{}{}\n\nCommit: {}\nBranch: \
+         {}\nClone:\n{}\n\n{assignees}{depends_on}{reviews}Timeline:\n{}{}",
+        utils::smart_wrap(&metadata.subject, 70),
+        usernames[&pr.pubkey],
+        format_labels(&metadata),
+        utils::smart_wrap(&pr.content, 80),
+        tip.pr_commit().unwrap_or("N/A"),
+        pr.pr_branch_name().unwrap_or("N/A"),
+        utils::format_iter(tip.pr_clones()),
+        crate::cli::pr::format_timeline(&pr, &updates, &statuses, &comments, &usernames),
+        comments_page
+            .map(|page| comments::format_comments(pr.id, &comments, &usernames, page))
+            .unwrap_or_default()
     );
     Ok(())
 }
+
+/// Returns the assignees of the issue or PR as a list with a header, or an
+/// empty string if it has no assignees.
+async fn format_assignees(client: &NostrClient, metadata: &IssuePrMetadata) -> String {
+    if metadata.assignees.is_empty() {
+        return String::new();
+    }
+    let usernames = client
+        .get_usernames(metadata.assignees.iter().copied())
+        .await;
+    format!(
+        "Assignees:\n{}\n\n",
+        utils::format_iter(metadata.assignees.iter().map(|user| &usernames[user]))
+    )
+}
+
+/// Returns the wrapped labels of the issue or PR followed by an empty line, or
+/// a single newline if it has no labels.
+fn format_labels(metadata: &IssuePrMetadata) -> String {
+    let labels = utils::smart_wrap(&metadata.format_labels(), 70);
+    if labels.is_empty() {
+        "\n".to_owned()
+    } else {
+        format!("{labels}\n\n")
+    }
+}
@@ -79,6 +79,20 @@ pub struct CliConfig {
     /// Signs events using the browser's NIP-07 extension.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub nip07:              Option<SocketAddr>,
+    /// The policy that patches and pull requests must satisfy to be merged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merge_policy:       Option<MergePolicy>,
+}
+
+/// The reviews a patch or pull request needs before it can be merged.
+#[derive(serde::Serialize, serde::Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MergePolicy {
+    /// The minimum number of maintainers approvals.
+    #[serde(default)]
+    pub min_approvals:        u8,
+    /// Whether a maintainer request for changes blocks the merge.
+    #[serde(default)]
+    pub no_requested_changes: bool,
 }
 
 /// A named group of repositories and relays.
@@ -147,6 +161,14 @@ impl CliConfig {
     }
 }
 
+impl MergePolicy {
+    /// Returns true if the policy doesn't require anything
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.min_approvals == 0 && !self.no_requested_changes
+    }
+}
+
 impl RepoRelaySet {
     /// Create a new [`RepoRelaySet`]
     pub fn new(
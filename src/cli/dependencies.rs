@@ -0,0 +1,290 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use std::collections::{HashSet, VecDeque};
+
+use nostr::{
+    event::{Event, Kind},
+    hashes::sha1::Hash as Sha1Hash,
+    key::PublicKey,
+    nips::nip19::ToBech32,
+};
+
+use super::{
+    CliOptions,
+    common_commands,
+    patch::{self, GitPatch},
+    traits::{OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+    types::{EntityType, NaddrOrSet, NostrEvent, PatchPrStatus},
+    utils as cli_utils,
+    verdicts,
+};
+use crate::{
+    error::{N34Error, N34Result},
+    git_utils,
+    nostr_utils::{
+        NostrClient,
+        traits::{GitIssuePrMetadata, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+/// Returns the status of the patch or PR, only considering the status events
+/// of the maintainers and its author.
+pub async fn fetch_status(
+    client: &NostrClient,
+    event: &Event,
+    maintainers: &[PublicKey],
+) -> N34Result<PatchPrStatus> {
+    let authorized_pubkeys = [maintainers, &[event.pubkey]].concat();
+    if event.kind == Kind::GitPatch {
+        let (root, root_revision) = common_commands::get_patch_root_revision(event)?;
+        client
+            .fetch_patch_status(root, root_revision, authorized_pubkeys)
+            .await
+    } else {
+        client.fetch_pr_status(event.id, authorized_pubkeys).await
+    }
+}
+
+/// Fetches the dependency chain of the patch or PR with their statuses. The
+/// direct dependencies come first, followed by their own dependencies.
+/// Dependencies that can't be found are skipped.
+pub async fn fetch_dependencies(
+    client: &NostrClient,
+    event: &Event,
+    maintainers: &[PublicKey],
+) -> N34Result<Vec<(Event, PatchPrStatus)>> {
+    let mut visited = HashSet::from([event.id]);
+    let mut pending = VecDeque::from(event.depends_on());
+    let mut dependencies = Vec::new();
+
+    while let Some(event_id) = pending.pop_front() {
+        if !visited.insert(event_id) {
+            continue;
+        }
+        let Some(dependency) = client.fetch_patch_or_pr(event_id).await? else {
+            tracing::warn!("Can't find the dependency `{event_id}`");
+            continue;
+        };
+        let status = fetch_status(client, &dependency, maintainers).await?;
+        pending.extend(dependency.depends_on());
+        dependencies.push((dependency, status));
+    }
+
+    Ok(dependencies)
+}
+
+/// Formats the dependency with its status, subject and ID
+pub fn format_dependency(event: &Event, status: &PatchPrStatus) -> String {
+    let subject = if event.kind == Kind::GitPatch {
+        GitPatch::from_event(event)
+            .map(|p| p.subject)
+            .unwrap_or_else(|_| "N/A".to_owned())
+    } else {
+        event.extract_event_subject().to_owned()
+    };
+
+    format!(
+        "({status}) {subject} ({})",
+        event.id.to_bech32().expect("Infallible")
+    )
+}
+
+/// Checks that the dependencies of the patch or PR are merged before merging
+/// it. The unmerged ones are shown, and if `interactive` the user is asked
+/// whether to merge it anyway.
+pub async fn check_merged_dependencies(
+    client: &NostrClient,
+    event: &Event,
+    maintainers: &[PublicKey],
+    interactive: bool,
+) -> N34Result<()> {
+    let unmerged = fetch_dependencies(client, event, maintainers)
+        .await?
+        .into_iter()
+        .filter(|(_, status)| !status.is_merged_or_applied())
+        .collect::<Vec<_>>();
+    if unmerged.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "Warning: merging ahead of unmerged dependencies:\n{}",
+        utils::format_iter(unmerged.iter().map(|(e, s)| format_dependency(e, s)))
+    );
+    if interactive && !cli_utils::prompt_bool("Merge anyway? [y/n]")? {
+        return Err(N34Error::UnmergedDependencies);
+    }
+
+    Ok(())
+}
+
+/// Offers to mark the open dependants of the merged patch or PR as merged if
+/// they are included in the merge commit, or shows how to rebase the PRs of
+/// the user onto the merge commit otherwise. Nothing is asked in
+/// non-interactive mode.
+pub async fn handle_dependants(
+    options: &CliOptions,
+    naddrs: Option<Vec<NaddrOrSet>>,
+    base: &Event,
+    merge_commit: &Sha1Hash,
+) -> N34Result<()> {
+    let naddrs_arg = naddrs;
+    let naddrs = utils::naddrs_or_file(
+        naddrs_arg.flat_naddrs(&options.config.sets)?,
+        &utils::nostr_address_path()?,
+    )?;
+    let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+    let client = NostrClient::init(options, &relays).await;
+    client.add_relays(&naddrs.extract_relays()).await;
+    let coordinates = naddrs.clone().into_coordinates();
+    let repos = client.fetch_repos(&coordinates).await?;
+    let maintainers = repos.extract_maintainers();
+    client.add_relays(&repos.extract_relays()).await;
+    let user_pubk = client.pubkey().await?;
+
+    let merge_commit_hex = merge_commit.to_string();
+    for dependant in client.fetch_dependants(&coordinates, base.id).await? {
+        let status = fetch_status(&client, &dependant, &maintainers).await?;
+        if !status.is_open() && !status.is_drafted() {
+            continue;
+        }
+        println!("Dependant: {}", format_dependency(&dependant, &status));
+        if options.non_interactive {
+            continue;
+        }
+
+        let is_patch = dependant.kind == Kind::GitPatch;
+        let pr_update = if is_patch {
+            None
+        } else {
+            client.fetch_pr_update(&dependant).await?
+        };
+        let tip = pr_update.as_ref().unwrap_or(&dependant);
+        let included = if is_patch {
+            let patches = client
+                .fetch_ordered_patch_series(dependant.clone())
+                .await?
+                .iter()
+                .map(GitPatch::from_event)
+                .collect::<N34Result<Vec<_>>>()?;
+            patch::merged_patches_tip(&patches, &merge_commit_hex).is_ok()
+        } else {
+            tip.pr_commit().is_some_and(|commit| {
+                git_utils::is_ancestor(commit, &merge_commit_hex).unwrap_or(false)
+            })
+        };
+
+        if included {
+            if let Some(violations) = merge_policy_violations(
+                options,
+                &client,
+                &dependant,
+                &[naddrs.extract_owners(), maintainers.clone()].concat(),
+            )
+            .await?
+            {
+                println!(
+                    "It's included in the merge commit, but the merge policy is not satisfied: \
+                     {violations}"
+                );
+            } else if cli_utils::prompt_bool(
+                "It's included in the merge commit, mark it as merged? [y/n]",
+            )? {
+                mark_merged(options, naddrs_arg.clone(), &dependant, *merge_commit).await?;
+            }
+        } else if !is_patch && dependant.pubkey == user_pubk {
+            println!(
+                "It's not included in the merge commit, rebase it onto the merge commit then \
+                 update it:\n  git rebase --onto {merge_commit_hex} <old-base> <branch>\n  n34 pr \
+                 update {} ...",
+                dependant.id.to_bech32().expect("Infallible")
+            );
+        } else {
+            println!("It's not included in the merge commit, its author may need to rebase it");
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the merge policy violations of the patch or PR, `None` if there is
+/// no merge policy or it's satisfied.
+async fn merge_policy_violations(
+    options: &CliOptions,
+    client: &NostrClient,
+    event: &Event,
+    maintainers: &[PublicKey],
+) -> N34Result<Option<String>> {
+    let Some(policy) = &options.config.merge_policy else {
+        return Ok(None);
+    };
+    let verdicts = if event.kind == Kind::GitPatch {
+        let (root_id, _) = common_commands::get_patch_root_revision(event)?;
+        verdicts::fetch_patch_verdicts(client, root_id).await?
+    } else {
+        verdicts::fetch_pr_verdicts(client, event).await?
+    };
+
+    match verdicts::check_merge_policy(policy, &verdicts, maintainers, &event.pubkey, false) {
+        Ok(_) => Ok(None),
+        Err(N34Error::MergePolicyNotSatisfied(violations)) => Ok(Some(violations)),
+        Err(err) => Err(err),
+    }
+}
+
+/// Marks the patch or PR as merged in the given merge commit
+async fn mark_merged(
+    options: &CliOptions,
+    naddrs: Option<Vec<NaddrOrSet>>,
+    event: &Event,
+    merge_commit: Sha1Hash,
+) -> N34Result<()> {
+    let event_id = NostrEvent {
+        event_id: event.id,
+        relays:   Vec::new(),
+    };
+    let merge_commit = Some(either::Either::Left(merge_commit));
+
+    if event.kind == Kind::GitPatch {
+        common_commands::patch_pr_status_command::<{ EntityType::Patch as u8 }>(
+            options,
+            event_id,
+            naddrs,
+            PatchPrStatus::MergedApplied,
+            merge_commit,
+            Vec::new(),
+            None,
+            None,
+            |_| Ok(()),
+        )
+        .await
+    } else {
+        common_commands::patch_pr_status_command::<{ EntityType::PullRequest as u8 }>(
+            options,
+            event_id,
+            naddrs,
+            PatchPrStatus::MergedApplied,
+            merge_commit,
+            Vec::new(),
+            None,
+            None,
+            |_| Ok(()),
+        )
+        .await
+    }
+}
@@ -0,0 +1,255 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use nostr::{
+    event::{Kind, Tag, TagKind},
+    filter::Filter,
+    nips::nip01::Coordinate,
+};
+
+use super::{labels, utils as cli_utils};
+use crate::{
+    error::{N34Error, N34Result},
+    nostr_utils::{NostrClient, utils},
+};
+
+/// The directory of the issue templates, next to the `nostr-address` file
+pub const ISSUE_TEMPLATES_DIR: &str = ".nostr/issue-templates";
+/// The repository announcement tag of a published issue template, its values
+/// are the template name and content
+pub const ISSUE_TEMPLATE_TAG: &str = "issue-template";
+
+/// An issue template, a markdown file with an optional front matter:
+///
+/// ```markdown
+/// ---
+/// name: Bug report
+/// about: Something is not working
+/// title: "bug: "
+/// labels: bug, triage
+/// ---
+/// The issue body
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueTemplate {
+    /// The template identifier, its file name without the extension
+    pub id:     String,
+    /// The human readable name of the template
+    pub name:   String,
+    /// A short description of when to use the template
+    pub about:  Option<String>,
+    /// The issue subject to start from
+    pub title:  Option<String>,
+    /// The default labels of the issue, without the `#` prefix
+    pub labels: Vec<String>,
+    /// The issue body to start from
+    pub body:   String,
+    /// The raw content of the template file
+    pub raw:    String,
+}
+
+impl IssueTemplate {
+    /// Parses the template content. Front matter keys other than `name`,
+    /// `about`, `title` and `labels` are ignored.
+    pub fn parse(id: impl Into<String>, content: &str) -> Self {
+        let id = id.into();
+        let normalized = content.replace("\r\n", "\n");
+        let (front_matter, body) = normalized
+            .strip_prefix("---\n")
+            .and_then(|rest| rest.split_once("\n---\n"))
+            .unwrap_or(("", &normalized));
+
+        let mut template = Self {
+            name: id.clone(),
+            id,
+            about: None,
+            title: None,
+            labels: Vec::new(),
+            body: body.trim().to_owned(),
+            raw: content.to_owned(),
+        };
+        for (key, value) in front_matter.lines().filter_map(|l| l.split_once(':')) {
+            let value = value.trim().trim_matches(['"', '\'']).to_owned();
+            match key.trim() {
+                "name" => template.name = value,
+                "about" => template.about = Some(value),
+                "title" => template.title = Some(value),
+                "labels" => {
+                    template.labels = labels::normalize_labels(
+                        value
+                            .trim_matches(['[', ']'])
+                            .split(',')
+                            .map(|l| l.trim().trim_matches(['"', '\'']).to_owned())
+                            .collect(),
+                    )
+                }
+                _ => {}
+            }
+        }
+        template
+    }
+
+    /// Returns the editor pre content of the template, the title on the first
+    /// line then the body. The template name is used if it has no title.
+    pub fn editor_content(&self) -> String {
+        format!(
+            "{}\n\n{}",
+            self.title.as_deref().unwrap_or(&self.name),
+            self.body
+        )
+    }
+
+    /// Returns the repository announcement tag of the template
+    pub fn to_tag(&self) -> Tag {
+        Tag::custom(
+            TagKind::custom(ISSUE_TEMPLATE_TAG),
+            [self.id.clone(), self.raw.clone()],
+        )
+    }
+
+    /// Parses the template from a repository announcement tag, `None` if it's
+    /// not an issue template tag.
+    pub fn from_tag(tag: &Tag) -> Option<Self> {
+        match tag.as_slice() {
+            [kind, id, content, ..] if kind == ISSUE_TEMPLATE_TAG => Some(Self::parse(id, content)),
+            _ => None,
+        }
+    }
+
+    /// Returns the template name and its description, if any
+    fn display_name(&self) -> String {
+        match &self.about {
+            Some(about) => format!("{} - {about}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Path to the issue templates directory in current directory, next to the
+/// `nostr-address` file.
+pub fn templates_dir() -> std::io::Result<PathBuf> {
+    utils::nostr_address_path().map(|path| path.with_file_name(ISSUE_TEMPLATES_DIR))
+}
+
+/// Reads the markdown issue templates of the directory, sorted by their id.
+/// Returns an empty vector if the directory doesn't exist.
+pub fn local_templates(dir: &Path) -> N34Result<Vec<IssueTemplate>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut templates = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        templates.push(IssueTemplate::parse(id, &fs::read_to_string(&path)?));
+    }
+    templates.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(templates)
+}
+
+/// Fetches the issue templates published with the repositories announcements
+pub async fn fetch_repos_templates(
+    client: &NostrClient,
+    coordinates: &[Coordinate],
+) -> N34Result<Vec<IssueTemplate>> {
+    let mut templates = Vec::new();
+    for coordinate in coordinates {
+        let Some(announcement) = client
+            .fetch_event(
+                Filter::new()
+                    .author(coordinate.public_key)
+                    .identifier(&coordinate.identifier)
+                    .kind(Kind::GitRepoAnnouncement),
+            )
+            .await?
+        else {
+            continue;
+        };
+        templates.extend(announcement.tags.iter().filter_map(IssueTemplate::from_tag));
+    }
+    Ok(templates)
+}
+
+/// Merges the local and the published templates, the local ones override the
+/// published ones with the same id.
+pub fn merge_templates(
+    local: Vec<IssueTemplate>,
+    published: Vec<IssueTemplate>,
+) -> Vec<IssueTemplate> {
+    let mut templates = local;
+    for template in published {
+        if !templates.iter().any(|t| t.id == template.id) {
+            templates.push(template);
+        }
+    }
+    templates
+}
+
+/// Returns the template to use. The template with the given id if `id` is
+/// given, otherwise asks the user to pick one of the templates or a blank
+/// issue when `interactive` is set.
+pub fn pick_template(
+    templates: Vec<IssueTemplate>,
+    id: Option<&str>,
+    interactive: bool,
+) -> N34Result<Option<IssueTemplate>> {
+    if let Some(id) = id {
+        let available = templates
+            .iter()
+            .map(|t| format!("`{}`", t.id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        return templates
+            .into_iter()
+            .find(|t| t.id == id)
+            .map(Some)
+            .ok_or_else(|| {
+                N34Error::IssueTemplateNotFound(
+                    id.to_owned(),
+                    if available.is_empty() {
+                        "none".to_owned()
+                    } else {
+                        available
+                    },
+                )
+            });
+    }
+
+    if templates.is_empty() || !interactive {
+        return Ok(None);
+    }
+    let options = templates
+        .iter()
+        .map(IssueTemplate::display_name)
+        .chain(["Blank issue".to_owned()])
+        .collect::<Vec<_>>();
+    let choice = cli_utils::prompt_choice("Choose an issue template", &options)?;
+    Ok(templates.into_iter().nth(choice))
+}
@@ -0,0 +1,68 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use super::*;
+
+#[test]
+fn template_front_matter() {
+    let template = IssueTemplate::parse(
+        "bug",
+        "---\nname: Bug report\nabout: Something is not working\ntitle: \"bug: \"\nlabels: [bug, \
+         \"#triage\"]\n---\n\n## Steps to reproduce\n",
+    );
+    assert_eq!(template.id, "bug");
+    assert_eq!(template.name, "Bug report");
+    assert_eq!(template.about.as_deref(), Some("Something is not working"));
+    assert_eq!(template.labels, vec!["bug".to_owned(), "triage".to_owned()]);
+    assert_eq!(template.body, "## Steps to reproduce");
+    assert_eq!(template.editor_content(), "bug: \n\n## Steps to reproduce");
+
+    let plain = IssueTemplate::parse("feature", "Describe the feature");
+    assert_eq!(plain.name, "feature");
+    assert!(plain.labels.is_empty());
+    assert_eq!(plain.editor_content(), "feature\n\nDescribe the feature");
+
+    assert_eq!(IssueTemplate::from_tag(&template.to_tag()), Some(template));
+}
+
+#[test]
+fn template_picking() {
+    let local = vec![IssueTemplate::parse(
+        "bug",
+        "---\nname: Local bug\n---\nbody",
+    )];
+    let published = vec![
+        IssueTemplate::parse("bug", "---\nname: Published bug\n---\nbody"),
+        IssueTemplate::parse("feature", "body"),
+    ];
+    let templates = merge_templates(local, published);
+    assert_eq!(
+        templates
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect::<Vec<_>>(),
+        ["Local bug", "feature"]
+    );
+
+    assert_eq!(
+        pick_template(templates.clone(), Some("feature"), false)
+            .unwrap()
+            .map(|t| t.id),
+        Some("feature".to_owned())
+    );
+    assert!(pick_template(templates.clone(), Some("docs"), false).is_err());
+    assert!(pick_template(templates, None, false).unwrap().is_none());
+}
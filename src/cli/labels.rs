@@ -0,0 +1,317 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+#[cfg(test)]
+mod tests;
+
+use nostr::{
+    event::{Event, EventBuilder, Kind, Tag, TagKind, TagStandard},
+    filter::Filter,
+    key::PublicKey,
+};
+
+use super::{
+    CliOptions,
+    traits::{OptionNaddrOrSetVecExt, RelayOrSetVecExt},
+    types::{NaddrOrSet, NostrEvent},
+};
+use crate::{
+    error::{N34Error, N34Result},
+    nostr_utils::{
+        NostrClient,
+        traits::{GitIssuePrMetadata, NaddrsUtils, ReposUtils},
+        utils,
+    },
+};
+
+/// The NIP-32 namespace of the labels added to an issue or PR, the same as
+/// its `t` tags.
+pub const LABEL_NAMESPACE: &str = "#t";
+/// The NIP-32 namespace of the labels removed from an issue or PR.
+pub const REMOVED_LABEL_NAMESPACE: &str = "removed-label";
+/// The NIP-32 namespace of the new subject of an issue or PR.
+pub const SUBJECT_NAMESPACE: &str = "#subject";
+/// The NIP-32 namespace of the users assigned to an issue or PR, the labels
+/// are hex public keys.
+pub const ASSIGNEE_NAMESPACE: &str = "assignee";
+/// The NIP-32 namespace of the users unassigned from an issue or PR.
+pub const UNASSIGNED_NAMESPACE: &str = "unassigned";
+
+/// The subject, labels and assignees of an issue or PR, after applying its
+/// label events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssuePrMetadata {
+    /// The subject, "N/A" if it has none
+    pub subject:   String,
+    /// The labels, without the `#` prefix
+    pub labels:    Vec<String>,
+    /// The assigned users
+    pub assignees: Vec<PublicKey>,
+}
+
+impl IssuePrMetadata {
+    /// Returns the subject, labels and assignees of the issue or PR after
+    /// applying the given label events in order. Labels in the
+    /// [`LABEL_NAMESPACE`] are added, the ones in the
+    /// [`REMOVED_LABEL_NAMESPACE`] are removed and the latest label in the
+    /// [`SUBJECT_NAMESPACE`] replaces the subject.
+    ///
+    /// Users in the [`ASSIGNEE_NAMESPACE`] are assigned and the ones in the
+    /// [`UNASSIGNED_NAMESPACE`] are unassigned, only by the label events of the
+    /// repositories `owners` and `maintainers`.
+    pub fn from_events(
+        event: &Event,
+        label_events: &[Event],
+        owners: &[PublicKey],
+        maintainers: &[PublicKey],
+    ) -> Self {
+        let mut subject = event.extract_event_subject().to_owned();
+        let mut labels = event
+            .tags
+            .filter(TagKind::t())
+            .filter_map(|t| t.content().map(str::to_owned))
+            .collect::<Vec<_>>();
+
+        let mut assignees: Vec<PublicKey> = Vec::new();
+
+        for label_event in label_events {
+            let is_maintainer =
+                owners.contains(&label_event.pubkey) || maintainers.contains(&label_event.pubkey);
+            for tag in label_event.tags.iter() {
+                let Some(TagStandard::Label {
+                    value,
+                    namespace: Some(namespace),
+                }) = tag.as_standardized()
+                else {
+                    continue;
+                };
+                match namespace.as_str() {
+                    LABEL_NAMESPACE if !labels.contains(value) => labels.push(value.clone()),
+                    REMOVED_LABEL_NAMESPACE => labels.retain(|label| label != value),
+                    SUBJECT_NAMESPACE => subject = value.clone(),
+                    ASSIGNEE_NAMESPACE | UNASSIGNED_NAMESPACE if is_maintainer => {
+                        let Ok(user) = PublicKey::from_hex(value) else {
+                            continue;
+                        };
+                        assignees.retain(|assignee| assignee != &user);
+                        if namespace == ASSIGNEE_NAMESPACE {
+                            assignees.push(user);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Self {
+            subject,
+            labels,
+            assignees,
+        }
+    }
+
+    /// Returns the labels formatted as comma-separated hashtags (e.g. "#bug,
+    /// #feature")
+    pub fn format_labels(&self) -> String {
+        self.labels
+            .iter()
+            .map(|label| format!("#{label}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Removes the `#` prefix of the labels
+pub fn normalize_labels(labels: Vec<String>) -> Vec<String> {
+    labels
+        .into_iter()
+        .map(|label| label.trim().trim_start_matches('#').to_owned())
+        .filter(|label| !label.is_empty())
+        .collect()
+}
+
+/// Fetches the label events of the issue or PR published by its author or the
+/// repositories `owners` and `maintainers`, and returns its subject, labels and
+/// assignees after applying them.
+pub async fn fetch_metadata(
+    client: &NostrClient,
+    event: &Event,
+    owners: &[PublicKey],
+    maintainers: &[PublicKey],
+) -> N34Result<IssuePrMetadata> {
+    let label_events = client
+        .fetch_label_events(event.id, [owners, maintainers, &[event.pubkey]].concat())
+        .await?;
+    Ok(IssuePrMetadata::from_events(
+        event,
+        &label_events,
+        owners,
+        maintainers,
+    ))
+}
+
+/// Publishes a NIP-32 label event for the issue or PR with the given labels in
+/// the `namespace`. Only the label events of the repository owners and
+/// maintainers and the issue or PR author are considered.
+pub async fn label_command<const IS_PR: bool>(
+    options: CliOptions,
+    event_id: NostrEvent,
+    naddrs: Option<Vec<NaddrOrSet>>,
+    namespace: &str,
+    labels: Vec<String>,
+) -> N34Result<()> {
+    publish_label_event::<IS_PR>(options, event_id, naddrs, namespace, labels, Vec::new()).await
+}
+
+/// Publishes a NIP-32 label event assigning the users to the issue or PR, or
+/// unassigning them if `unassign` is set. Only the assignments of the
+/// repository owners and maintainers are considered.
+pub async fn assign_command<const IS_PR: bool>(
+    options: CliOptions,
+    event_id: NostrEvent,
+    naddrs: Option<Vec<NaddrOrSet>>,
+    users: Vec<PublicKey>,
+    unassign: bool,
+) -> N34Result<()> {
+    let namespace = if unassign {
+        UNASSIGNED_NAMESPACE
+    } else {
+        ASSIGNEE_NAMESPACE
+    };
+    publish_label_event::<IS_PR>(
+        options,
+        event_id,
+        naddrs,
+        namespace,
+        users.iter().map(PublicKey::to_hex).collect(),
+        users,
+    )
+    .await
+}
+
+/// Publishes a NIP-32 label event for the issue or PR with the given labels in
+/// the `namespace`, mentioning the `mentions` users.
+async fn publish_label_event<const IS_PR: bool>(
+    options: CliOptions,
+    event_id: NostrEvent,
+    naddrs: Option<Vec<NaddrOrSet>>,
+    namespace: &str,
+    labels: Vec<String>,
+    mentions: Vec<PublicKey>,
+) -> N34Result<()> {
+    let is_assignment = matches!(namespace, ASSIGNEE_NAMESPACE | UNASSIGNED_NAMESPACE);
+    let naddrs = utils::check_empty_naddrs(utils::naddrs_or_file(
+        naddrs.flat_naddrs(&options.config.sets)?,
+        &utils::nostr_address_path()?,
+    )?)?;
+    let relays = options.relays.clone().flat_relays(&options.config.sets)?;
+    let client = NostrClient::init(&options, &relays).await;
+    let user_pubk = client.pubkey().await?;
+    client
+        .add_relays(&[naddrs.extract_relays(), event_id.relays].concat())
+        .await;
+
+    let owners = naddrs.extract_owners();
+    let coordinates = naddrs.clone().into_coordinates();
+    let repos = client.fetch_repos(&coordinates).await?;
+    let maintainers = repos.extract_maintainers();
+    let relay_hint = repos.extract_relays().first().cloned();
+    client.add_relays(&repos.extract_relays()).await;
+
+    let event = client
+        .fetch_event(Filter::new().id(event_id.event_id).kind(
+            const {
+                if IS_PR {
+                    crate::cli::pr::PR_KIND
+                } else {
+                    Kind::GitIssue
+                }
+            },
+        ))
+        .await?
+        .ok_or(
+            const {
+                if IS_PR {
+                    N34Error::CanNotFoundPr
+                } else {
+                    N34Error::CanNotFoundIssue
+                }
+            },
+        )?;
+    let authorized_pubkeys = [maintainers.as_slice(), &[event.pubkey], &owners].concat();
+    if is_assignment && !maintainers.contains(&user_pubk) && !owners.contains(&user_pubk) {
+        tracing::warn!("You are not a maintainer, your assignments will be ignored by `n34`");
+    } else if !authorized_pubkeys.contains(&user_pubk) {
+        tracing::warn!(
+            "You are not a maintainer or the author, your labels will be ignored by `n34`"
+        );
+    }
+
+    let label_event = EventBuilder::new(Kind::Label, "")
+        .pow(options.pow.unwrap_or_default())
+        .tag(Tag::from_standardized_without_cell(
+            TagStandard::LabelNamespace(namespace.to_owned()),
+        ))
+        .tags(labels.into_iter().map(|label| {
+            Tag::from_standardized_without_cell(TagStandard::Label {
+                value:     label,
+                namespace: Some(namespace.to_owned()),
+            })
+        }))
+        .tag(Tag::custom(
+            TagKind::e(),
+            [
+                event.id.to_hex(),
+                relay_hint
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            ],
+        ))
+        .tag(Tag::public_key(event.pubkey))
+        .tags(mentions.iter().copied().map(Tag::public_key))
+        .tags(
+            coordinates
+                .into_iter()
+                .map(|c| Tag::coordinate(c, relay_hint.clone())),
+        )
+        .dedup_tags()
+        .build(user_pubk);
+
+    let label_event_id = label_event.id.expect("There is an id");
+    let user_relays_list = client.user_relays_list(user_pubk).await?;
+    let write_relays = [
+        relays,
+        naddrs.extract_relays(),
+        repos.extract_relays(),
+        utils::add_write_relays(user_relays_list.as_ref()),
+        client.read_relays_from_users(&authorized_pubkeys).await,
+        client.read_relays_from_users(&mentions).await,
+    ]
+    .concat();
+
+    let success = client
+        .send_event_to(label_event, user_relays_list.as_ref(), &write_relays)
+        .await?;
+    let nevent = utils::new_nevent(label_event_id, &success)?;
+    if is_assignment {
+        println!("Assignment event created: {nevent}");
+    } else {
+        println!("Label event created: {nevent}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use nostr::{
+    event::{Kind, Tag, TagStandard},
+    key::Keys,
+};
+
+use super::*;
+use crate::cli::test_utils::signed_event;
+
+/// Returns the NIP-32 label tags of the values in the namespace
+fn label_tags(namespace: &str, values: &[String]) -> Vec<Tag> {
+    values
+        .iter()
+        .map(|value| {
+            Tag::from_standardized_without_cell(TagStandard::Label {
+                value:     value.clone(),
+                namespace: Some(namespace.to_owned()),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn metadata_from_label_events() {
+    let keys = Keys::generate();
+    let issue = signed_event(
+        &keys,
+        Kind::GitIssue,
+        "body",
+        [
+            Tag::hashtag("bug"),
+            Tag::from_standardized_without_cell(TagStandard::Subject("Old subject".to_owned())),
+        ],
+        0,
+    );
+    let label_event = |namespace: &str, labels: &[&str], at: u64| {
+        let labels = labels.iter().map(|l| (*l).to_owned()).collect::<Vec<_>>();
+        signed_event(&keys, Kind::Label, "", label_tags(namespace, &labels), at)
+    };
+
+    assert_eq!(
+        IssuePrMetadata::from_events(&issue, &[], &[], &[]),
+        IssuePrMetadata {
+            subject:   "Old subject".to_owned(),
+            labels:    vec!["bug".to_owned()],
+            assignees: Vec::new(),
+        }
+    );
+
+    let events = [
+        label_event(LABEL_NAMESPACE, &["feature", "bug"], 1),
+        label_event(REMOVED_LABEL_NAMESPACE, &["bug"], 2),
+        label_event(SUBJECT_NAMESPACE, &["First subject"], 3),
+        label_event(SUBJECT_NAMESPACE, &["New subject"], 4),
+        label_event(LABEL_NAMESPACE, &["good-first-issue"], 5),
+    ];
+    let metadata = IssuePrMetadata::from_events(&issue, &events, &[], &[]);
+    assert_eq!(metadata.subject, "New subject");
+    assert_eq!(metadata.format_labels(), "#feature, #good-first-issue");
+}
+
+#[test]
+fn assignees_from_label_events() {
+    let author = Keys::generate();
+    let maintainer = Keys::generate();
+    let alice = Keys::generate().public_key();
+    let bob = Keys::generate().public_key();
+    let issue = signed_event(&author, Kind::GitIssue, "body", [], 0);
+    let assign_event = |keys: &Keys, namespace: &str, users: &[PublicKey], at: u64| {
+        let users = users.iter().map(PublicKey::to_hex).collect::<Vec<_>>();
+        signed_event(keys, Kind::Label, "", label_tags(namespace, &users), at)
+    };
+
+    let events = [
+        assign_event(&maintainer, ASSIGNEE_NAMESPACE, &[alice, bob], 1),
+        // Only the maintainers assignments count
+        assign_event(&author, UNASSIGNED_NAMESPACE, &[bob], 2),
+        assign_event(&maintainer, UNASSIGNED_NAMESPACE, &[alice], 3),
+    ];
+    let metadata = IssuePrMetadata::from_events(&issue, &events, &[], &[maintainer.public_key()]);
+    assert_eq!(metadata.assignees, vec![bob]);
+    // The repositories owners too, even if they are not maintainers
+    assert_eq!(
+        IssuePrMetadata::from_events(&issue, &events, &[maintainer.public_key()], &[]).assignees,
+        vec![bob]
+    );
+    assert!(
+        IssuePrMetadata::from_events(&issue, &events, &[], &[])
+            .assignees
+            .is_empty()
+    );
+}
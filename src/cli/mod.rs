@@ -16,24 +16,39 @@
 
 /// Commands module
 pub mod commands;
+/// Threaded comments of issues and pull requests
+pub mod comments;
 /// Common commands used by multiply commands
 pub mod common_commands;
 /// The CLI config
 pub mod config;
 /// Default lazy values for CLI arguments
 pub mod defaults;
+/// Dependencies between patches and pull requests
+pub mod dependencies;
+/// Issue templates of the repositories
+pub mod issue_templates;
+/// Labels and subject edits of issues and pull requests
+pub mod labels;
 /// Macros for CLI application.
 pub mod macros;
 /// Represents the state used for CLI options.
 pub mod options_state;
 /// CLI arguments parsers
 pub mod parsers;
+/// Local records of the sent patch series
+pub mod series_records;
+/// Helpers shared by the CLI tests
+#[cfg(test)]
+pub mod test_utils;
 /// CLI traits
 pub mod traits;
 /// Common helper types used throughout the CLI.
 pub mod types;
 /// CLI utils
 pub mod utils;
+/// Review verdicts of patches and pull requests
+pub mod verdicts;
 
 
 use clap::Parser;
@@ -14,6 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
 
+#[cfg(test)]
+mod tests;
+
 use std::{
     collections::HashSet,
     fs,
@@ -27,6 +30,7 @@ use nostr::{
         nip19::{FromBech32, Nip19Coordinate, ToBech32},
         nip46::NostrConnectURI,
     },
+    types::Timestamp,
 };
 use serde::{Deserialize, Serialize, Serializer};
 
@@ -104,6 +108,62 @@ pub fn name_and_sha1(value: &str) -> Result<(String, Sha1Hash), String> {
     ))
 }
 
+/// Parses a pull request clone URL. A SHA-1 hash is rejected, since the tip
+/// commit was given before the clones before it moved to `--commit`.
+pub fn parse_clone_url(value: &str) -> Result<nostr::Url, String> {
+    if value.parse::<Sha1Hash>().is_ok() {
+        return Err(format!(
+            "`{value}` is a commit, not a clone URL. The pull request tip is given with `--commit \
+             <REV>` now, and defaults to `HEAD`"
+        ));
+    }
+    nostr::Url::parse(value).map_err(|err| format!("Invalid clone URL `{value}`: {err}"))
+}
+
+/// Parses a date, either relative to now like `2w` or a `YYYY-MM-DD` date.
+/// See [`parse_date_at`].
+pub fn parse_date(value: &str) -> Result<Timestamp, String> {
+    parse_date_at(value, Timestamp::now())
+}
+
+/// Parses a date, either relative to `now` like `2w` or a `YYYY-MM-DD` date in
+/// UTC. The relative date units are `h` for hours, `d` for days, `w` for
+/// weeks, `m` for months of 30 days and `y` for years of 365 days.
+pub fn parse_date_at(value: &str, now: Timestamp) -> Result<Timestamp, String> {
+    let value = value.trim();
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%F") {
+        let secs = date
+            .and_hms_opt(0, 0, 0)
+            .expect("Valid time")
+            .and_utc()
+            .timestamp();
+        return u64::try_from(secs)
+            .map(Timestamp::from)
+            .map_err(|_| format!("The date `{value}` is before 1970"));
+    }
+
+    let invalid = || {
+        format!(
+            "Invalid date `{value}`, must be a `YYYY-MM-DD` date or a relative date like `2w`, \
+             `3d` or `12h`"
+        )
+    };
+    let (unit_idx, unit) = value.char_indices().last().ok_or_else(invalid)?;
+    let unit_secs = match unit {
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        'm' => 30 * 24 * 60 * 60,
+        'y' => 365 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    let count: u64 = value[..unit_idx].parse().map_err(|_| invalid())?;
+
+    Ok(Timestamp::from(
+        now.as_u64().saturating_sub(count.saturating_mul(unit_secs)),
+    ))
+}
+
 /// Serializes a set of NIP-19 coordinates as a list of bech32 strings.
 pub fn ser_naddrs<S>(naddr: &HashSet<Nip19Coordinate>, serializer: S) -> Result<S::Ok, S::Error>
 where
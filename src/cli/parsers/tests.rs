@@ -0,0 +1,45 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use super::*;
+
+#[test]
+fn commits_are_not_clone_urls() {
+    assert!(parse_clone_url("https://example.com/repo.git").is_ok());
+    assert!(parse_clone_url("864f3018f62ab2e1265edb670d5493dafe7d2cb2").is_err());
+    assert!(parse_clone_url("not a url").is_err());
+}
+
+#[test]
+fn relative_and_absolute_dates() {
+    let now = Timestamp::from(100 * 24 * 60 * 60);
+    assert_eq!(
+        parse_date_at("2w", now).unwrap(),
+        Timestamp::from(86 * 24 * 60 * 60)
+    );
+    assert_eq!(
+        parse_date_at("12h", now).unwrap(),
+        Timestamp::from(99 * 24 * 60 * 60 + 12 * 60 * 60)
+    );
+    assert_eq!(parse_date_at("1y", now).unwrap(), Timestamp::from(0));
+    assert_eq!(
+        parse_date_at("1970-01-02", now).unwrap(),
+        Timestamp::from(24 * 60 * 60)
+    );
+    assert!(parse_date_at("2", now).is_err());
+    assert!(parse_date_at("w", now).is_err());
+    assert!(parse_date_at("2x", now).is_err());
+}
@@ -0,0 +1,127 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+#[cfg(test)]
+mod tests;
+
+use std::{fs, path::PathBuf};
+
+use nostr::event::EventId;
+
+use crate::error::{N34Error, N34Result};
+
+/// The series records file name, inside the n34 data directory.
+const SERIES_RECORDS_FILE: &str = "series.toml";
+
+/// Local records of the patch series sent by `patch send`, used to find the
+/// original patch of a reroll.
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+pub struct SeriesRecords {
+    /// Path to the records file (not serialized)
+    #[serde(skip)]
+    path:   PathBuf,
+    /// The sent series
+    #[serde(default, rename = "series", skip_serializing_if = "Vec::is_empty")]
+    series: Vec<SeriesRecord>,
+}
+
+/// A patch series sent to a repository.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct SeriesRecord {
+    /// The repository coordinate, `30617:<pubkey>:<identifier>`
+    pub repo:     String,
+    /// The branch the series was sent from, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch:   Option<String>,
+    /// The series subject, without the `[PATCH ...]` prefix
+    pub subject:  String,
+    /// The root patch of the first version of the series
+    pub original: EventId,
+    /// The root patch of the latest sent version
+    pub latest:   EventId,
+    /// The latest sent version
+    pub version:  u32,
+}
+
+impl SeriesRecords {
+    /// Reads the series records from the n34 data directory. Returns empty
+    /// records if the file doesn't exist.
+    pub fn load() -> N34Result<Self> {
+        let path = dirs::data_local_dir()
+            .ok_or(N34Error::CanNotFindDataPath)?
+            .join("n34")
+            .join(SERIES_RECORDS_FILE);
+        tracing::debug!(path = %path.display(), "Loading the series records");
+
+        let mut records: Self = if path.exists() {
+            toml::from_str(&fs::read_to_string(&path)?).map_err(N34Error::ParseSeriesRecords)?
+        } else {
+            Self::default()
+        };
+        records.path = path;
+
+        Ok(records)
+    }
+
+    /// Writes the series records to its file
+    pub fn dump(&self) -> N34Result<()> {
+        if let Some(parent) = self.path.parent()
+            && !parent.exists()
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(
+            &self.path,
+            toml::to_string_pretty(self).map_err(N34Error::SerializeSeriesRecords)?,
+        )?;
+        Ok(())
+    }
+
+    /// Finds the latest series of the given repository by its subject,
+    /// preferring the one sent from the same branch. If no series has the
+    /// subject, the series of the branch is used, as long as it's the only one
+    /// sent from it.
+    pub fn find(&self, repo: &str, branch: Option<&str>, subject: &str) -> Option<&SeriesRecord> {
+        let repo_series = || self.series.iter().rev().filter(|s| s.repo == repo);
+        let same_branch = |s: &&SeriesRecord| branch.is_some() && s.branch.as_deref() == branch;
+
+        repo_series()
+            .filter(|s| s.subject == subject)
+            .find(same_branch)
+            .or_else(|| repo_series().find(|s| s.subject == subject))
+            .or_else(|| {
+                let mut branch_series = repo_series().filter(same_branch);
+                branch_series
+                    .next()
+                    .filter(|_| branch_series.next().is_none())
+            })
+    }
+
+    /// Records a sent series. If the series has an original patch, the record
+    /// of that original patch is updated, otherwise a new record is added.
+    pub fn record(&mut self, record: SeriesRecord) {
+        if let Some(existing) = self
+            .series
+            .iter_mut()
+            .find(|s| s.repo == record.repo && s.original == record.original)
+        {
+            *existing = record;
+        } else {
+            self.series.push(record);
+        }
+    }
+}
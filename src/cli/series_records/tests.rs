@@ -0,0 +1,49 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+
+use super::*;
+
+#[test]
+fn find_by_subject_first() {
+    let record = |branch: &str, subject: &str, original: u8| {
+        SeriesRecord {
+            repo:     "30617:pubkey:n34".to_owned(),
+            branch:   Some(branch.to_owned()),
+            subject:  subject.to_owned(),
+            original: EventId::from_byte_array([original; 32]),
+            latest:   EventId::from_byte_array([original; 32]),
+            version:  1,
+        }
+    };
+    let mut records = SeriesRecords::default();
+    records.record(record("feat", "Add x", 1));
+    records.record(record("feat", "Add y", 2));
+    records.record(record("fix", "Fix z", 3));
+
+    let find = |branch, subject| {
+        records
+            .find("30617:pubkey:n34", branch, subject)
+            .map(|r| r.original.as_bytes()[0])
+    };
+    assert_eq!(find(Some("feat"), "Add x"), Some(1));
+    assert_eq!(find(Some("other"), "Add x"), Some(1));
+    assert_eq!(find(None, "Add y"), Some(2));
+    // Two series of the branch, the subject can't be guessed
+    assert_eq!(find(Some("feat"), "Add x and more"), None);
+    assert_eq!(find(Some("fix"), "Fix z and w"), Some(3));
+    assert_eq!(find(None, "Unknown"), None);
+}
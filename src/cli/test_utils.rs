@@ -0,0 +1,37 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use nostr::{
+    event::{Event, EventBuilder, Kind, Tag},
+    key::Keys,
+    types::Timestamp,
+};
+
+/// Builds an event with the given tags and creation time, then signs it with
+/// the keys.
+pub fn signed_event(
+    keys: &Keys,
+    kind: Kind,
+    content: impl Into<String>,
+    tags: impl IntoIterator<Item = Tag>,
+    created_at: u64,
+) -> Event {
+    EventBuilder::new(kind, content)
+        .tags(tags)
+        .custom_created_at(Timestamp::from(created_at))
+        .sign_with_keys(keys)
+        .expect("Signing with keys can't fail")
+}
@@ -16,7 +16,11 @@
 
 use std::time::Duration;
 
-use nostr::{event::EventId, nips::nip19::Nip19Coordinate, types::RelayUrl};
+use nostr::{
+    event::{EventId, Tag},
+    nips::nip19::Nip19Coordinate,
+    types::RelayUrl,
+};
 use tokio::io::AsyncReadExt;
 
 use super::CliOptions;
@@ -29,6 +33,7 @@ use crate::{
         utils,
     },
     error::{N34Error, N34Result},
+    nostr_utils,
 };
 
 /// A trait defining the interface for command runners in the CLI.
@@ -51,6 +56,14 @@ impl Vec<NostrEvent> {
     pub fn into_event_ids(self) -> Vec<EventId> {
         self.into_iter().map(|e| e.event_id).collect()
     }
+
+    /// Converts each `NostrEvent` into a `depends-on` tag, using its first
+    /// relay as the hint.
+    pub fn into_depends_on_tags(self) -> Vec<Tag> {
+        self.into_iter()
+            .map(|e| nostr_utils::utils::depends_on_tag(&e.event_id, e.relays.first()))
+            .collect()
+    }
 }
 
 #[easy_ext::ext(NaddrOrSetVecExt)]
@@ -14,10 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
 
+#[cfg(test)]
+mod tests;
+
 use std::{fmt, str::FromStr};
 
 use nostr::{
     event::{EventId, Kind},
+    key::PublicKey,
     nips::{
         nip01::Coordinate,
         nip05::{Nip05Address, Nip05Profile},
@@ -65,6 +69,18 @@ pub enum EntityType {
     Issue,
 }
 
+/// The order of the listed issues, patches and PRs.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ListSort {
+    /// By their creation time, the newest first
+    Created,
+    /// By their latest activity time, the most recently updated first
+    Updated,
+    /// By their status, then by their creation time
+    #[default]
+    Status,
+}
+
 /// Parses and represents a Nostr `nevent1` or `note1`.
 #[derive(Debug, Clone)]
 pub struct NostrEvent {
@@ -75,6 +91,15 @@ pub struct NostrEvent {
     pub relays:   Vec<RelayUrl>,
 }
 
+/// A user public key or `me` for the signer user.
+#[derive(Debug, Clone, Copy)]
+pub enum UserOrMe {
+    /// The signer user.
+    Me,
+    /// A user public key.
+    User(PublicKey),
+}
+
 #[derive(Debug)]
 pub struct EchoAuthUrl;
 
@@ -240,6 +265,40 @@ impl FromStr for RelayOrSet {
     }
 }
 
+impl FromStr for UserOrMe {
+    type Err = String;
+
+    /// Parses `me` as the signer user, otherwise a public key in `npub` or hex
+    /// format.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let user = s.trim().trim_start_matches("nostr:");
+        if user == "me" {
+            Ok(Self::Me)
+        } else {
+            PublicKey::parse(user)
+                .map(Self::User)
+                .map_err(|err| err.to_string())
+        }
+    }
+}
+
+impl FromStr for ListSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "created" => Ok(Self::Created),
+            "updated" => Ok(Self::Updated),
+            "status" => Ok(Self::Status),
+            _ => {
+                Err(format!(
+                    "Invalid sort `{s}`, must be `created`, `updated` or `status`"
+                ))
+            }
+        }
+    }
+}
+
 impl FromStr for NostrEvent {
     type Err = String;
 
@@ -259,7 +318,7 @@ impl FromStr for NostrEvent {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum PatchPrStatus {
     /// The patch/pr is currently open
     Open,
@@ -342,6 +401,27 @@ impl fmt::Display for PatchPrStatus {
 }
 
 
+impl FromStr for PatchPrStatus {
+    type Err = String;
+
+    /// Parses the status name. `merged`, `applied` and `resolved` are the same
+    /// status, `resolved` is used for issues.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "open" => Ok(Self::Open),
+            "merged" | "applied" | "resolved" => Ok(Self::MergedApplied),
+            "closed" => Ok(Self::Closed),
+            "draft" => Ok(Self::Draft),
+            _ => {
+                Err(format!(
+                    "Invalid status `{s}`, must be one of `open`, `merged`, `applied`, \
+                     `resolved`, `closed` or `draft`"
+                ))
+            }
+        }
+    }
+}
+
 impl TryFrom<Kind> for PatchPrStatus {
     type Error = N34Error;
 
@@ -0,0 +1,38 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use super::*;
+
+#[test]
+fn list_status_and_sort_parsing() {
+    assert!(matches!(
+        PatchPrStatus::from_str("Resolved"),
+        Ok(PatchPrStatus::MergedApplied)
+    ));
+    assert!(matches!(
+        PatchPrStatus::from_str("draft"),
+        Ok(PatchPrStatus::Draft)
+    ));
+    assert_eq!(
+        PatchPrStatus::from_str("pending").unwrap_err(),
+        "Invalid status `pending`, must be one of `open`, `merged`, `applied`, `resolved`, \
+         `closed` or `draft`"
+    );
+    assert!(matches!(
+        ListSort::from_str("updated"),
+        Ok(ListSort::Updated)
+    ));
+}
@@ -0,0 +1,319 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, fmt};
+
+use nostr::{
+    event::{Event, EventId, Tag, TagKind, TagStandard},
+    filter::Alphabet,
+    key::PublicKey,
+};
+
+use crate::{
+    cli::MergePolicy,
+    error::{N34Error, N34Result},
+    nostr_utils::{NostrClient, traits::GitIssuePrMetadata},
+};
+
+/// The NIP-32 namespace of the review verdict labels
+pub const VERDICT_NAMESPACE: &str = "review";
+
+/// The verdict of a review comment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewVerdict {
+    /// The changes are approved
+    Approve,
+    /// The changes need more work before merging
+    RequestChanges,
+    /// A review comment without a verdict
+    Comment,
+}
+
+impl ReviewVerdict {
+    /// Returns the label of the verdict
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approve => "approve",
+            Self::RequestChanges => "request-changes",
+            Self::Comment => "comment",
+        }
+    }
+
+    /// Returns the verdict from its label, `None` if it's unknown
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "approve" => Some(Self::Approve),
+            "request-changes" => Some(Self::RequestChanges),
+            "comment" => Some(Self::Comment),
+            _ => None,
+        }
+    }
+
+    /// Returns the verdict of the review comment from its label, `None` if
+    /// it's not a review comment.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        event.tags.iter().find_map(|tag| {
+            match tag.as_standardized()? {
+                TagStandard::Label { value, namespace }
+                    if namespace.as_deref() == Some(VERDICT_NAMESPACE) =>
+                {
+                    Self::from_label(value)
+                }
+                _ => None,
+            }
+        })
+    }
+
+    /// Returns the NIP-32 label tags of the verdict
+    pub fn tags(&self) -> [Tag; 2] {
+        [
+            Tag::from_standardized_without_cell(TagStandard::LabelNamespace(
+                VERDICT_NAMESPACE.to_owned(),
+            )),
+            Tag::from_standardized_without_cell(TagStandard::Label {
+                value:     self.as_str().to_owned(),
+                namespace: Some(VERDICT_NAMESPACE.to_owned()),
+            }),
+        ]
+    }
+
+    /// Returns the verdict of the given command flags, `None` if none of them
+    /// is set.
+    pub fn from_flags(approve: bool, request_changes: bool, comment: bool) -> Option<Self> {
+        [
+            (approve, Self::Approve),
+            (request_changes, Self::RequestChanges),
+            (comment, Self::Comment),
+        ]
+        .into_iter()
+        .find_map(|(flag, verdict)| flag.then_some(verdict))
+    }
+
+    /// Returns the default content of the review comment of the verdict. `None`
+    /// for comment verdicts, they need a content.
+    pub const fn default_content(&self) -> Option<&'static str> {
+        match self {
+            Self::Approve => Some("Approved"),
+            Self::RequestChanges => Some("Changes requested"),
+            Self::Comment => None,
+        }
+    }
+
+    /// Returns the verdict in the past tense, e.g. `approved`
+    pub const fn past_tense(&self) -> &'static str {
+        match self {
+            Self::Approve => "approved",
+            Self::RequestChanges => "requested changes",
+            Self::Comment => "commented",
+        }
+    }
+
+    /// Check if the verdict is an approval
+    #[inline]
+    pub fn is_approve(&self) -> bool {
+        matches!(self, Self::Approve)
+    }
+
+    /// Check if the verdict requests changes
+    #[inline]
+    pub fn is_request_changes(&self) -> bool {
+        matches!(self, Self::RequestChanges)
+    }
+}
+
+impl fmt::Display for ReviewVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Returns the current verdict of each reviewer, the latest approval or
+/// request for changes. Comment verdicts don't change the reviewer verdict.
+/// Sorted by the verdict time.
+pub fn current_verdicts(comments: &[Event]) -> Vec<(PublicKey, ReviewVerdict)> {
+    let mut verdicts = comments
+        .iter()
+        .filter_map(|comment| {
+            ReviewVerdict::from_event(comment)
+                .filter(|verdict| !matches!(verdict, ReviewVerdict::Comment))
+                .map(|verdict| (comment, verdict))
+        })
+        .collect::<Vec<_>>();
+    verdicts.sort_by_key(|(comment, _)| comment.created_at);
+
+    let mut latest: HashMap<PublicKey, usize> = HashMap::new();
+    for (idx, (comment, _)) in verdicts.iter().enumerate() {
+        latest.insert(comment.pubkey, idx);
+    }
+
+    verdicts
+        .iter()
+        .enumerate()
+        .filter(|(idx, (comment, _))| latest.get(&comment.pubkey) == Some(idx))
+        .map(|(_, (comment, verdict))| (comment.pubkey, *verdict))
+        .collect()
+}
+
+/// Returns the commit of the PR tip that the review comment refers to
+pub fn reviewed_commit(comment: &Event) -> Option<&str> {
+    comment
+        .tags
+        .find(TagKind::single_letter(Alphabet::C, false))
+        .and_then(|t| t.content())
+}
+
+/// Fetches the current verdicts of the PR reviewers, only considering the
+/// reviews of the current tip.
+pub async fn fetch_pr_verdicts(
+    client: &NostrClient,
+    pr: &Event,
+) -> N34Result<Vec<(PublicKey, ReviewVerdict)>> {
+    let pr_update = client.fetch_pr_update(pr).await?;
+    let Some(commit) = pr_update.as_ref().unwrap_or(pr).pr_commit() else {
+        return Ok(Vec::new());
+    };
+    let comments = client
+        .fetch_comments(pr.id)
+        .await?
+        .into_iter()
+        .filter(|comment| reviewed_commit(comment) == Some(commit))
+        .collect::<Vec<_>>();
+
+    Ok(current_verdicts(&comments))
+}
+
+/// Fetches the current verdicts of the patch reviewers, only considering the
+/// reviews of its latest revision.
+pub async fn fetch_patch_verdicts(
+    client: &NostrClient,
+    root_id: EventId,
+) -> N34Result<Vec<(PublicKey, ReviewVerdict)>> {
+    let latest = client
+        .fetch_patch_revisions(root_id)
+        .await?
+        .last()
+        .map_or(root_id, |revision| revision.id);
+
+    Ok(current_verdicts(&client.fetch_comments(latest).await?))
+}
+
+/// Formats the number of approvals and requests for changes, e.g. `2
+/// approved, 1 requested changes`. `None` if there are no verdicts.
+pub fn format_verdicts_summary(verdicts: &[(PublicKey, ReviewVerdict)]) -> Option<String> {
+    let approvals = verdicts.iter().filter(|(_, v)| v.is_approve()).count();
+    let changes = verdicts
+        .iter()
+        .filter(|(_, v)| v.is_request_changes())
+        .count();
+
+    let summary = [
+        (approvals, ReviewVerdict::Approve),
+        (changes, ReviewVerdict::RequestChanges),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count != 0)
+    .map(|(count, verdict)| format!("{count} {}", verdict.past_tense()))
+    .collect::<Vec<_>>();
+
+    (!summary.is_empty()).then(|| summary.join(", "))
+}
+
+/// Formats the verdict of each reviewer, e.g. `Awiteb: approved`. Reviewers
+/// without a name in `usernames` are shown as `Unknown`.
+pub fn format_verdicts(
+    verdicts: &[(PublicKey, ReviewVerdict)],
+    usernames: &HashMap<PublicKey, String>,
+) -> Vec<String> {
+    verdicts
+        .iter()
+        .map(|(reviewer, verdict)| {
+            format!(
+                "{}: {}",
+                usernames
+                    .get(reviewer)
+                    .map(String::as_str)
+                    .unwrap_or("Unknown"),
+                verdict.past_tense()
+            )
+        })
+        .collect()
+}
+
+/// Returns the requirements of the merge policy that the verdicts don't
+/// satisfy. Only the verdicts of the maintainers count, except the author of
+/// the patch or PR.
+pub fn merge_policy_violations(
+    policy: &MergePolicy,
+    verdicts: &[(PublicKey, ReviewVerdict)],
+    maintainers: &[PublicKey],
+    author: &PublicKey,
+) -> Vec<String> {
+    let maintainers_verdicts = verdicts
+        .iter()
+        .filter(|(reviewer, _)| reviewer != author && maintainers.contains(reviewer))
+        .map(|(_, verdict)| verdict)
+        .collect::<Vec<_>>();
+    let approvals = maintainers_verdicts
+        .iter()
+        .filter(|v| v.is_approve())
+        .count();
+    let requested_changes = maintainers_verdicts
+        .iter()
+        .filter(|v| v.is_request_changes())
+        .count();
+
+    let mut violations = Vec::new();
+    if approvals < usize::from(policy.min_approvals) {
+        violations.push(format!(
+            "{approvals} of {} required maintainers approvals",
+            policy.min_approvals
+        ));
+    }
+    if policy.no_requested_changes && requested_changes != 0 {
+        violations.push(format!(
+            "{requested_changes} of the maintainers requested changes"
+        ));
+    }
+    violations
+}
+
+/// Checks that the verdicts satisfy the merge policy. If it's not satisfied
+/// and `force` is set, returns a note about overriding it to add to the status
+/// content.
+pub fn check_merge_policy(
+    policy: &MergePolicy,
+    verdicts: &[(PublicKey, ReviewVerdict)],
+    maintainers: &[PublicKey],
+    author: &PublicKey,
+    force: bool,
+) -> N34Result<Option<String>> {
+    let violations = merge_policy_violations(policy, verdicts, maintainers, author);
+    if violations.is_empty() {
+        return Ok(None);
+    }
+
+    let violations = violations.join(", ");
+    if !force {
+        return Err(N34Error::MergePolicyNotSatisfied(violations));
+    }
+    tracing::warn!("Overriding the merge policy: {violations}");
+    Ok(Some(format!(
+        "Merged with `--force`, overriding the merge policy: {violations}"
+    )))
+}
@@ -0,0 +1,114 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use nostr::{event::Kind, key::Keys};
+
+use super::*;
+use crate::cli::test_utils::signed_event;
+
+#[test]
+fn current_review_verdicts() {
+    let alice = Keys::generate();
+    let bob = Keys::generate();
+    let review = |keys: &Keys, verdict: Option<ReviewVerdict>, at: u64| {
+        signed_event(
+            keys,
+            Kind::Comment,
+            "",
+            verdict.into_iter().flat_map(|v| v.tags()),
+            at,
+        )
+    };
+
+    // Relays may return the comments in any order
+    let comments = [
+        review(&alice, Some(ReviewVerdict::Approve), 300),
+        review(&bob, Some(ReviewVerdict::Approve), 100),
+        review(&alice, Some(ReviewVerdict::RequestChanges), 200),
+        review(&bob, Some(ReviewVerdict::Comment), 400),
+        review(&bob, None, 500),
+    ];
+
+    assert_eq!(
+        ReviewVerdict::from_event(&comments[3]),
+        Some(ReviewVerdict::Comment)
+    );
+    assert_eq!(ReviewVerdict::from_event(&comments[4]), None);
+    assert_eq!(
+        current_verdicts(&comments),
+        vec![
+            (bob.public_key(), ReviewVerdict::Approve),
+            (alice.public_key(), ReviewVerdict::Approve),
+        ]
+    );
+
+    let changes = review(&bob, Some(ReviewVerdict::RequestChanges), 600);
+    let current = current_verdicts(&[comments.as_slice(), &[changes]].concat());
+    assert_eq!(
+        format_verdicts_summary(&current),
+        Some("1 approved, 1 requested changes".to_owned())
+    );
+    assert_eq!(format_verdicts_summary(&[]), None);
+}
+
+#[test]
+fn merge_policy_violations_of_verdicts() {
+    let author = Keys::generate().public_key();
+    let maintainer = Keys::generate().public_key();
+    let other_maintainer = Keys::generate().public_key();
+    let stranger = Keys::generate().public_key();
+    let maintainers = [author, maintainer, other_maintainer];
+    let policy = MergePolicy {
+        min_approvals:        2,
+        no_requested_changes: true,
+    };
+
+    // The author and non-maintainers approvals don't count
+    let approvals = [
+        (author, ReviewVerdict::Approve),
+        (maintainer, ReviewVerdict::Approve),
+        (stranger, ReviewVerdict::Approve),
+    ];
+    assert_eq!(
+        merge_policy_violations(&policy, &approvals, &maintainers, &author),
+        vec!["1 of 2 required maintainers approvals".to_owned()]
+    );
+
+    let changes = [
+        (maintainer, ReviewVerdict::Approve),
+        (other_maintainer, ReviewVerdict::RequestChanges),
+        (stranger, ReviewVerdict::RequestChanges),
+    ];
+    assert_eq!(
+        merge_policy_violations(&policy, &changes, &maintainers, &author),
+        vec![
+            "1 of 2 required maintainers approvals".to_owned(),
+            "1 of the maintainers requested changes".to_owned()
+        ]
+    );
+    assert!(check_merge_policy(&policy, &changes, &maintainers, &author, false).is_err());
+    assert!(
+        check_merge_policy(&policy, &changes, &maintainers, &author, true)
+            .unwrap()
+            .is_some_and(|note| note.starts_with("Merged with `--force`"))
+    );
+
+    let satisfied = [
+        (maintainer, ReviewVerdict::Approve),
+        (other_maintainer, ReviewVerdict::Approve),
+    ];
+    assert!(merge_policy_violations(&policy, &satisfied, &maintainers, &author).is_empty());
+}
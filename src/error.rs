@@ -168,6 +168,54 @@ pub enum N34Error {
     PushRejectedByGraspServers,
     #[error("The repository doesn't contains any GRASP servers")]
     NoGraspServer,
+    #[error("This command must be run inside a git repository")]
+    NotGitRepo,
+    #[error("`git {0}` failed: {1}")]
+    GitCommand(String, String),
+    #[error("Can't fetch the pull request tip `{0}` from any of its clone URLs")]
+    CanNotFetchPrTip(String),
+    #[error(
+        "The branch `{0}` has commits that are not in the pull request, use `--force` to \
+         overwrite it"
+    )]
+    BranchDiverged(String),
+    #[error("Can't find the branch to push to, please check out a branch or specify it")]
+    PushBranchNotFound,
+    #[error("Invalid clone URL `{0}`")]
+    InvalidCloneUrl(String),
+    #[error("The pull request doesn't have a tip commit")]
+    PrWithoutCommit,
+    #[error("`{0}` is neither the pull request nor one of its updates")]
+    NotPrRevision(String),
+    #[error("Can't find the default branch of the repository, please specify it")]
+    DefaultBranchNotFound,
+    #[error("The merge commit `{0}` is not in the local repository, fetch it first")]
+    MergeCommitNotFound(String),
+    #[error("No merge commit on `HEAD` includes `{0}`, please specify it")]
+    NoMergeCommit(String),
+    #[error("The merge commit `{0}` is not reachable from the default branch `{1}`")]
+    MergeCommitNotInDefaultBranch(String, String),
+    #[error("The merge commit `{0}` doesn't include `{1}`")]
+    MergeCommitWithoutTip(String, String),
+    #[error("Can't find the patch `{0}` in the last {1} commits of `{2}`")]
+    PatchNotInHistory(String, usize, String),
+    #[error("Aborted, the dependencies are not merged yet")]
+    UnmergedDependencies,
+    #[error("The merge policy is not satisfied: {0}. Use `--force` to merge anyway")]
+    MergePolicyNotSatisfied(String),
+    #[error("No review comments found. Write your comments between the quoted patch lines")]
+    EmptyReview,
+    #[error("Failed to parse the series records file: {0}")]
+    ParseSeriesRecords(toml::de::Error),
+    #[error("Couldn't serialize the series records. This is a bug, please report it: {0}")]
+    SerializeSeriesRecords(toml::ser::Error),
+    #[error("Can't find the issue template `{0}`. Available templates: {1}")]
+    IssueTemplateNotFound(String, String),
+    #[error(
+        "The patches were written, but these don't recreate the commits in their `commit` tags: \
+         {0}"
+    )]
+    CommitsMismatch(String),
 }
 
 impl N34Error {
@@ -0,0 +1,608 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::error::{N34Error, N34Result};
+
+/// Header of the commit PGP signature in the commit object.
+const GPGSIG_HEADER: &str = "gpgsig ";
+
+/// A git command to run in the local repository.
+pub struct Git {
+    /// The underlying process command
+    command: Command,
+    /// Content to write to the command standard input
+    stdin:   Option<String>,
+}
+
+impl Git {
+    /// Creates a new git command with the given arguments.
+    pub fn new<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut command = Command::new("git");
+        command.args(args);
+        Self {
+            command,
+            stdin: None,
+        }
+    }
+
+    /// Runs the command inside the given directory instead of the current one.
+    pub fn current_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.command.current_dir(dir);
+        self
+    }
+
+    /// Appends an argument to the command.
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.command.arg(arg);
+        self
+    }
+
+    /// Sets an environment variable for the command.
+    pub fn env(mut self, key: &str, value: impl AsRef<OsStr>) -> Self {
+        self.command.env(key, value);
+        self
+    }
+
+    /// Writes the given content to the command standard input.
+    pub fn stdin(mut self, content: impl Into<String>) -> Self {
+        self.stdin = Some(content.into());
+        self
+    }
+
+    /// Runs the command and returns its trimmed stdout. Fails with the command
+    /// stderr if it exits unsuccessfully.
+    pub fn run(self) -> N34Result<String> {
+        let command_str = self.command_str();
+        let output = self.output()?;
+
+        if !output.status.success() {
+            return Err(N34Error::GitCommand(
+                command_str,
+                String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    /// Runs the command and returns whether it exits successfully. The command
+    /// output is discarded.
+    pub fn success(self) -> N34Result<bool> {
+        Ok(self.output()?.status.success())
+    }
+
+    /// Returns the command as a string, used in the error messages.
+    fn command_str(&self) -> String {
+        self.command
+            .get_args()
+            .map(|a| a.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Spawns the command, writes the stdin if any and waits for the output.
+    fn output(mut self) -> N34Result<std::process::Output> {
+        tracing::debug!("Running `git {}`", self.command_str());
+
+        let mut child = self
+            .command
+            .stdin(
+                if self.stdin.is_some() {
+                    Stdio::piped()
+                } else {
+                    Stdio::null()
+                },
+            )
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Write the stdin in another thread, so a command that writes to its
+        // stdout while reading doesn't block on a full pipe.
+        let stdin_writer = self
+            .stdin
+            .zip(child.stdin.take())
+            .map(|(content, mut stdin)| {
+                std::thread::spawn(move || stdin.write_all(content.as_bytes()))
+            });
+
+        let output = child.wait_with_output()?;
+        // A failed write means the command exited early, its exit status
+        // reports the failure.
+        if let Some(writer) = stdin_writer
+            && let Ok(Err(err)) = writer.join()
+        {
+            tracing::debug!("Failed to write the git stdin: {err}");
+        }
+        Ok(output)
+    }
+}
+
+/// The committer of a commit, as stored in the commit object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Committer {
+    /// The committer name
+    pub name:      String,
+    /// The committer email
+    pub email:     String,
+    /// Unix timestamp of the commit
+    pub timestamp: i64,
+    /// Timezone offset in minutes
+    pub offset:    i32,
+}
+
+/// A commit object found in the local repository.
+#[derive(Debug, Clone)]
+pub struct CommitObject {
+    /// The parent commits
+    pub parents:   Vec<String>,
+    /// The commit committer
+    pub committer: Option<Committer>,
+    /// The commit PGP signature, if it's signed
+    pub pgp_sig:   Option<String>,
+}
+
+/// A detached worktree in a temporary directory. It's removed when dropped.
+pub struct TempWorktree {
+    /// The temporary directory of the worktree
+    dir: tempfile::TempDir,
+}
+
+impl Committer {
+    /// Parses the committer from its commit object line, e.g. `Awiteb
+    /// <a@4rs.nl> 1748373642 +0300`.
+    pub fn from_line(line: &str) -> Option<Self> {
+        let (name, rest) = line.split_once(" <")?;
+        let (email, rest) = rest.split_once("> ")?;
+        let (timestamp, offset) = rest.trim().split_once(' ')?;
+        let (sign, offset) = offset.split_at_checked(1)?;
+        let hours: i32 = offset.get(..2)?.parse().ok()?;
+        let minutes: i32 = offset.get(2..)?.parse().ok()?;
+
+        Some(Self {
+            name:      name.to_owned(),
+            email:     email.to_owned(),
+            timestamp: timestamp.parse().ok()?,
+            offset:    if sign == "-" { -1 } else { 1 } * (hours * 60 + minutes),
+        })
+    }
+
+    /// Returns the committer date in git internal format, e.g. `1748373642
+    /// +0300`.
+    pub fn git_date(&self) -> String {
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let offset = self.offset.abs();
+        format!(
+            "{} {sign}{:02}{:02}",
+            self.timestamp,
+            offset / 60,
+            offset % 60
+        )
+    }
+}
+
+impl TempWorktree {
+    /// Creates a detached worktree of the current repository at the given
+    /// revision.
+    pub fn new(rev: &str) -> N34Result<Self> {
+        let dir = tempfile::TempDir::with_prefix("n34-")?;
+        Git::new(["worktree", "add", "--detach"])
+            .arg(dir.path())
+            .arg(rev)
+            .run()?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the worktree path
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+impl Drop for TempWorktree {
+    fn drop(&mut self) {
+        if let Err(err) = Git::new(["worktree", "remove", "--force"])
+            .arg(self.dir.path())
+            .run()
+        {
+            tracing::warn!("Failed to remove the temporary worktree: {err}");
+        }
+    }
+}
+
+/// Returns an error if the current directory is not inside a git work tree.
+pub fn ensure_work_tree() -> N34Result<()> {
+    if Git::new(["rev-parse", "--is-inside-work-tree"]).success()? {
+        Ok(())
+    } else {
+        Err(N34Error::NotGitRepo)
+    }
+}
+
+/// Returns the current branch name, or the `HEAD` commit if it's detached.
+pub fn current_ref() -> N34Result<String> {
+    let branch = Git::new(["rev-parse", "--abbrev-ref", "HEAD"]).run()?;
+    if branch == "HEAD" {
+        return Git::new(["rev-parse", "HEAD"]).run();
+    }
+    Ok(branch)
+}
+
+/// Returns the current branch name, `None` if `HEAD` is detached or if the
+/// current directory is not a git repository.
+pub fn current_branch() -> Option<String> {
+    Git::new(["symbolic-ref", "--short", "-q", "HEAD"])
+        .run()
+        .ok()
+        .filter(|branch| !branch.is_empty())
+}
+
+/// Creates a new branch starting at `start_point` and switches to it.
+pub fn checkout_new_branch(branch: &str, start_point: &str) -> N34Result<()> {
+    Git::new(["checkout", "-b", branch, start_point])
+        .run()
+        .map(|_| ())
+}
+
+/// Fetches the given refspec from the remote URL and returns the fetched
+/// commit.
+pub fn fetch(url: &str, refspec: &str) -> N34Result<String> {
+    Git::new([
+        "fetch",
+        "--no-tags",
+        "--quiet",
+        "--end-of-options",
+        url,
+        refspec,
+    ])
+    .run()?;
+    Git::new(["rev-parse", "FETCH_HEAD"]).run()
+}
+
+/// Returns whether the given local branch exists.
+pub fn branch_exists(branch: &str) -> N34Result<bool> {
+    Git::new(["rev-parse", "--verify", "-q"])
+        .arg(format!("refs/heads/{branch}"))
+        .success()
+}
+
+/// Returns whether the given name is a valid branch name.
+pub fn is_valid_branch_name(branch: &str) -> bool {
+    Git::new(["check-ref-format"])
+        .arg(format!("refs/heads/{branch}"))
+        .success()
+        .unwrap_or(false)
+}
+
+/// Returns whether `ancestor` is an ancestor of `commit`.
+pub fn is_ancestor(ancestor: &str, commit: &str) -> N34Result<bool> {
+    Git::new([
+        "merge-base",
+        "--is-ancestor",
+        "--end-of-options",
+        ancestor,
+        commit,
+    ])
+    .success()
+}
+
+/// Points the branch at the given commit then switches to it. If the branch
+/// is the current one, it's fast-forwarded instead.
+pub fn checkout_branch_at(branch: &str, commit: &str) -> N34Result<()> {
+    if current_branch().as_deref() == Some(branch) {
+        return Git::new(["merge", "--ff-only", "--end-of-options", commit])
+            .run()
+            .map(|_| ());
+    }
+    Git::new(["branch", "--force", "--end-of-options", branch, commit]).run()?;
+    Git::new(["switch", "--end-of-options", branch])
+        .run()
+        .map(|_| ())
+}
+
+/// Applies the given mailbox using `git am`, empty patches such as cover
+/// letters are dropped.
+pub fn am(mbox: impl Into<String>) -> N34Result<()> {
+    Git::new(["am", "--empty=drop"])
+        .stdin(mbox)
+        .run()
+        .map(|_| ())
+}
+
+/// Aborts the in-progress `git am` session then switches back to `original_ref`
+/// and deletes the `branch`.
+pub fn abort_am(original_ref: &str, branch: &str) -> N34Result<()> {
+    Git::new(["am", "--abort"]).run()?;
+    Git::new(["checkout", original_ref]).run()?;
+    Git::new(["branch", "-D", branch]).run().map(|_| ())
+}
+
+/// Generates the patches of the given revision range using `git format-patch`
+/// and returns them as a mailbox.
+pub fn format_patch(
+    range: &str,
+    cover_letter: bool,
+    reroll_count: Option<u32>,
+    base: Option<&str>,
+) -> N34Result<String> {
+    let mut args = vec!["format-patch".to_owned(), "--stdout".to_owned()];
+    if cover_letter {
+        args.push("--cover-letter".to_owned());
+    }
+    if let Some(count) = reroll_count {
+        args.push(format!("--reroll-count={count}"));
+    }
+    if let Some(base) = base {
+        args.push(format!("--base={base}"));
+    }
+    args.push(range.to_owned());
+
+    Git::new(args).run()
+}
+
+/// Reads the given commit from the local repository. Returns `None` if it's not
+/// found or if the current directory is not a git repository.
+pub fn commit_object(commit: &str) -> Option<CommitObject> {
+    let raw = Git::new(["cat-file", "commit", commit]).run().ok()?;
+    let headers = raw.split_once("\n\n").map_or(raw.as_str(), |(h, _)| h);
+
+    let mut parents = Vec::new();
+    let mut committer = None;
+    let mut pgp_sig: Option<String> = None;
+    let mut in_sig = false;
+    for line in headers.lines() {
+        if in_sig && let Some(sig_line) = line.strip_prefix(' ') {
+            let sig = pgp_sig.as_mut().expect("In the signature");
+            sig.push('\n');
+            sig.push_str(sig_line);
+            continue;
+        }
+        in_sig = false;
+
+        if let Some(parent) = line.strip_prefix("parent ") {
+            parents.push(parent.to_owned());
+        } else if let Some(line) = line.strip_prefix("committer ") {
+            committer = Committer::from_line(line);
+        } else if let Some(sig) = line.strip_prefix(GPGSIG_HEADER) {
+            pgp_sig = Some(sig.to_owned());
+            in_sig = true;
+        }
+    }
+
+    Some(CommitObject {
+        parents,
+        committer,
+        pgp_sig,
+    })
+}
+
+/// Applies the given patches with `git am` inside the given worktree, empty
+/// patches such as cover letters are dropped. If a committer is given, it's
+/// used as the commit committer to recreate the original commit. Returns the
+/// `HEAD` commit after applying them.
+pub fn am_in(dir: &Path, patch: &str, committer: Option<&Committer>) -> N34Result<String> {
+    let mut git = Git::new(["am", "--keep-cr", "--no-gpg-sign", "--empty=drop"])
+        .current_dir(dir)
+        .stdin(patch);
+    if let Some(committer) = committer {
+        git = git
+            .env("GIT_COMMITTER_NAME", &committer.name)
+            .env("GIT_COMMITTER_EMAIL", &committer.email)
+            .env("GIT_COMMITTER_DATE", committer.git_date());
+    }
+
+    if let Err(err) = git.run() {
+        _ = Git::new(["am", "--abort"]).current_dir(dir).run();
+        return Err(err);
+    }
+    Git::new(["rev-parse", "HEAD"]).current_dir(dir).run()
+}
+
+/// Adds the PGP signature to the `HEAD` commit of the given worktree, by
+/// writing a new commit object with the signature header then moving `HEAD`
+/// to it. Returns the signed commit.
+pub fn sign_head_in(dir: &Path, pgp_sig: &str) -> N34Result<String> {
+    let raw = Git::new(["cat-file", "commit", "HEAD"])
+        .current_dir(dir)
+        .run()?;
+    let (headers, message) = raw.split_once("\n\n").unwrap_or((raw.as_str(), ""));
+    let signed = format!(
+        "{headers}\n{GPGSIG_HEADER}{}\n\n{message}\n",
+        pgp_sig.replace('\n', "\n ")
+    );
+
+    let commit = Git::new(["hash-object", "-t", "commit", "-w", "--stdin"])
+        .current_dir(dir)
+        .stdin(signed)
+        .run()?;
+    Git::new(["reset", "--soft", &commit])
+        .current_dir(dir)
+        .run()?;
+    Ok(commit)
+}
+
+/// Returns the `git range-diff` between the two given revision ranges.
+pub fn range_diff(old_range: &str, new_range: &str) -> N34Result<String> {
+    Git::new(["range-diff", "--no-color", old_range, new_range]).run()
+}
+
+/// Returns the `git diff` between the two given commits, or its diffstat if
+/// `stat` is true.
+pub fn diff(from: &str, to: &str, stat: bool) -> N34Result<String> {
+    let mut git = Git::new(["diff", "--no-color", "--no-ext-diff"]);
+    if stat {
+        git = git.arg("--stat");
+    }
+    git.arg("--end-of-options").arg(from).arg(to).run()
+}
+
+/// Returns the local default branch, the branch that `origin/HEAD` points to,
+/// falling back to `main` or `master` if one of them exists.
+pub fn default_branch() -> N34Result<String> {
+    let remote_head = Git::new(["symbolic-ref", "--short", "-q", "refs/remotes/origin/HEAD"])
+        .run()
+        .ok();
+    let candidates = remote_head
+        .as_deref()
+        .and_then(|head| head.split_once('/'))
+        .map(|(_, branch)| branch.to_owned())
+        .into_iter()
+        .chain(["main".to_owned(), "master".to_owned()]);
+
+    for branch in candidates {
+        if Git::new(["rev-parse", "--verify", "-q"])
+            .arg(format!("refs/heads/{branch}"))
+            .success()?
+        {
+            return Ok(branch);
+        }
+    }
+
+    Err(N34Error::DefaultBranchNotFound)
+}
+
+/// Resolves the given revision to its commit sha.
+pub fn rev_parse_commit(rev: &str) -> N34Result<String> {
+    Git::new(["rev-parse", "--verify", "--end-of-options"])
+        .arg(format!("{rev}^{{commit}}"))
+        .run()
+}
+
+/// Returns the best common ancestor of the two commits.
+pub fn merge_base(first: &str, second: &str) -> N34Result<String> {
+    Git::new(["merge-base", "--end-of-options", first, second]).run()
+}
+
+/// Returns the commit that brought the given commit into `HEAD`, `None` if
+/// there is none. It's the commit itself if it's on the first-parent chain of
+/// `HEAD` (fast-forwarded or applied onto it), otherwise the first merge commit
+/// that has it in one of its merged (non-first) parents.
+pub fn merge_commit_including(commit: &str) -> N34Result<Option<String>> {
+    let commit = rev_parse_commit(commit)?;
+    if Git::new(["rev-list", "--first-parent", "HEAD"])
+        .run()?
+        .lines()
+        .any(|first_parent| first_parent == commit)
+    {
+        return Ok(Some(commit));
+    }
+
+    let merges = Git::new([
+        "rev-list",
+        "--merges",
+        "--ancestry-path",
+        "--reverse",
+        "--parents",
+    ])
+    .arg(format!("{commit}..HEAD"))
+    .run()?;
+    for merge in merges.lines() {
+        let mut commits = merge.split_whitespace();
+        let Some(merge) = commits.next() else {
+            continue;
+        };
+        // Skip the first parent, it's the branch the merge was made on
+        for parent in commits.skip(1) {
+            if is_ancestor(&commit, parent)? {
+                return Ok(Some(merge.to_owned()));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the messages of the commits in the given revision range, oldest
+/// first. Merge commits are skipped.
+pub fn commit_messages(range: &str) -> N34Result<Vec<String>> {
+    Ok(
+        Git::new(["log", "--reverse", "--no-merges", "--format=%B%x00", range])
+            .run()?
+            .split('\0')
+            .map(|message| message.trim().to_owned())
+            .filter(|message| !message.is_empty())
+            .collect(),
+    )
+}
+
+/// Returns the URL of the given remote, or the remote itself if it's already
+/// a URL.
+pub fn remote_url(remote: &str) -> N34Result<String> {
+    match Git::new(["remote", "get-url", remote]).run() {
+        Ok(url) => Ok(url),
+        Err(_) if remote.contains("://") => Ok(remote.to_owned()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Pushes the commit to the reference of the remote, which can be a remote
+/// name or a URL. Fails if the push is rejected. If not interactive, git and
+/// ssh will fail instead of prompting for the credentials.
+pub fn push(remote: &str, commit: &str, reference: &str, interactive: bool) -> N34Result<()> {
+    let mut git = Git::new(["push", "--quiet", "--end-of-options", remote])
+        .arg(format!("{commit}:{reference}"));
+    if !interactive {
+        git = git
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("GCM_INTERACTIVE", "never");
+        if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+            git = git.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+        }
+    }
+    git.run().map(|_| ())
+}
+
+/// Returns the stable patch IDs of the given patches, in the same order. See
+/// `git patch-id --stable`.
+pub fn patch_ids(patches: impl Into<String>) -> N34Result<Vec<String>> {
+    Ok(Git::new(["patch-id", "--stable"])
+        .stdin(patches)
+        .run()?
+        .lines()
+        .filter_map(|line| line.split_once(' ').map(|(id, _)| id.to_owned()))
+        .collect())
+}
+
+/// Returns the stable patch IDs of the last `depth` non-merge commits of the
+/// given branch, mapped to their commits.
+pub fn branch_patch_ids(branch: &str, depth: usize) -> N34Result<HashMap<String, String>> {
+    let log = Git::new(["log", "-p", "--no-merges", "--no-color", "--no-ext-diff"])
+        .arg(format!("-n{depth}"))
+        .arg(branch)
+        .run()?;
+
+    Ok(Git::new(["patch-id", "--stable"])
+        .stdin(format!("{log}\n"))
+        .run()?
+        .lines()
+        .filter_map(|line| {
+            line.split_once(' ')
+                .map(|(id, commit)| (id.to_owned(), commit.to_owned()))
+        })
+        .collect())
+}
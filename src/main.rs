@@ -18,6 +18,10 @@
 pub mod cli;
 /// N34 errors
 pub mod error;
+/// Git commands wrapper
+pub mod git_utils;
+/// Mailbox and patch email parsing
+pub mod mbox;
 /// Nostr keyring
 pub mod nostr_keyring;
 /// Nostr utils module
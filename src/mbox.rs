@@ -0,0 +1,577 @@
+// n34 - A CLI to interact with NIP-34 and other stuff related to code in Nostr
+// Copyright (C) 2025 Awiteb <a@4rs.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
+
+use std::sync::LazyLock;
+
+use base64::Engine;
+use regex::Regex;
+
+/// Regular expression for the mbox `From ` line that starts a message, e.g.
+/// `From <sha> Mon Sep 17 00:00:00 2001` from `git format-patch` or
+/// `From mboxrd@z Thu Jan  1 00:00:00 1970` from public-inbox.
+pub static FROM_LINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^From (?<sender>\S+) +\w{3} \w{3} +\d{1,2} \d{2}:\d{2}:\d{2}(?: [+-]\d{4})? \d{4}\r?$",
+    )
+    .unwrap()
+});
+
+/// Regular expression for an RFC 2047 encoded word, e.g.
+/// `=?UTF-8?q?caf=C3=A9?=`
+static ENCODED_WORD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"=\?(?<charset>[^?*]+)(?:\*[^?]*)?\?(?<encoding>[bBqQ])\?(?<text>[^?]*)\?=")
+        .unwrap()
+});
+
+/// Regular expression for a commit message trailer, e.g. `Signed-off-by: ...`
+static TRAILER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?<token>[A-Za-z0-9][A-Za-z0-9-]*): *(?<value>.*)$").unwrap());
+
+/// Regular expression for the diffstat summary line, e.g. ` 2 files changed, 3
+/// insertions(+), 1 deletion(-)`
+static DIFFSTAT_SUMMARY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)^ (?<files>\d+) files? changed(?:, (?<insertions>\d+) insertions?\(\+\))?(?:, (?<deletions>\d+) deletions?\(-\))?\r?$",
+    )
+    .unwrap()
+});
+
+/// Headers that `git am` accepts at the beginning of the message body, used by
+/// `git send-email` when the sender is not the patch author.
+const IN_BODY_HEADERS: [&str; 3] = ["From", "Date", "Subject"];
+
+/// The sha used by `git format-patch` for the messages that are not from a
+/// commit, like the cover letter.
+pub const NULL_SHA: &str = "0000000000000000000000000000000000000000";
+
+/// Keys that `git format-patch` writes in the message body but are not commit
+/// trailers.
+const NON_TRAILER_KEYS: [&str; 2] = ["base-commit", "prerequisite-patch-id"];
+
+/// An RFC 5322 message, from an mbox or a single patch file.
+#[derive(Debug, Clone, Default)]
+pub struct Message {
+    /// The sender of the mbox `From ` line. The commit sha in the `git
+    /// format-patch` output.
+    pub envelope: Option<String>,
+    /// The unfolded headers, without decoding
+    pub headers:  Vec<(String, String)>,
+    /// The decoded text body
+    pub body:     String,
+}
+
+/// The diffstat of a patch, from its summary line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diffstat {
+    /// The changed files, as written in the diffstat
+    pub files:      Vec<String>,
+    /// The number of changed files
+    pub changed:    u32,
+    /// The number of inserted lines
+    pub insertions: u32,
+    /// The number of deleted lines
+    pub deletions:  u32,
+}
+
+impl Message {
+    /// Parses a single message. The mbox `From ` line is optional.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let mut lines = raw.split('\n').peekable();
+        let envelope = lines
+            .next_if(|line| line.starts_with("From ") && !line.starts_with("From: "))
+            .map(|line| {
+                FROM_LINE_RE
+                    .captures(line)
+                    .and_then(|c| c.name("sender"))
+                    .map_or(line[5..].split(' ').next().unwrap_or_default(), |m| {
+                        m.as_str()
+                    })
+                    .to_owned()
+            });
+
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in lines.by_ref() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with([' ', '\t']) {
+                let (_, value) = headers
+                    .last_mut()
+                    .ok_or_else(|| format!("Header continuation without a header: `{line}`"))?;
+                value.push_str(line);
+                continue;
+            }
+            let (name, value) = line
+                .split_once(':')
+                .filter(|(name, _)| !name.is_empty() && !name.contains([' ', '\t']))
+                .ok_or_else(|| format!("Invalid header line: `{line}`"))?;
+            headers.push((name.to_owned(), value.trim().to_owned()));
+        }
+
+        if headers.is_empty() {
+            return Err("No headers found".to_owned());
+        }
+
+        let raw_body = lines.collect::<Vec<_>>().join("\n");
+        let mut message = Self {
+            envelope,
+            headers,
+            body: String::new(),
+        };
+        message.body = message.decode_body(&raw_body);
+        Ok(message)
+    }
+
+    /// Creates a plain text UTF-8 message with the given headers and body. The
+    /// header values must be encoded.
+    pub fn new_plain_text(
+        envelope: Option<String>,
+        headers: Vec<(String, String)>,
+        body: String,
+    ) -> Self {
+        let mut headers = headers;
+        headers.extend(
+            [
+                ("MIME-Version", "1.0"),
+                ("Content-Type", "text/plain; charset=UTF-8"),
+                ("Content-Transfer-Encoding", "8bit"),
+            ]
+            .map(|(name, value)| (name.to_owned(), value.to_owned())),
+        );
+
+        Self {
+            envelope,
+            headers,
+            body,
+        }
+    }
+
+    /// Formats the message like `git format-patch` does, with its mbox `From `
+    /// line. The body `From ` lines are escaped if it's for an mbox with
+    /// multiple messages.
+    pub fn format(&self, escape_body: bool) -> String {
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}\n"))
+            .collect::<String>();
+        let body = self.body.trim_end_matches('\n');
+
+        format!(
+            "From {} Mon Sep 17 00:00:00 2001\n{headers}\n{}\n",
+            self.envelope.as_deref().unwrap_or(NULL_SHA),
+            if escape_body {
+                escape_from_lines(body)
+            } else {
+                body.to_owned()
+            }
+        )
+    }
+
+    /// Returns the first header with the given name, undecoded. The name is
+    /// case-insensitive.
+    pub fn raw_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the first header with the given name, with its RFC 2047 encoded
+    /// words decoded. The name is case-insensitive.
+    pub fn header(&self, name: &str) -> Option<String> {
+        self.raw_header(name).map(decode_header)
+    }
+
+    /// Returns the message ID, without the angle brackets.
+    pub fn message_id(&self) -> Option<String> {
+        self.raw_header("Message-ID")
+            .map(|id| strip_angle_brackets(id).to_owned())
+    }
+
+    /// Returns the parameter of the given header, e.g. the `charset` of the
+    /// `Content-Type`.
+    fn header_param(&self, header: &str, param: &str) -> Option<String> {
+        self.raw_header(header)?
+            .split(';')
+            .skip(1)
+            .filter_map(|p| p.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(param))
+            .map(|(_, value)| value.trim().trim_matches('"').to_owned())
+    }
+
+    /// Decodes the body using its `Content-Transfer-Encoding` and charset. The
+    /// text parts of a multipart body are joined.
+    fn decode_body(&self, raw_body: &str) -> String {
+        let content_type = self
+            .raw_header("Content-Type")
+            .unwrap_or("text/plain")
+            .to_ascii_lowercase();
+
+        if content_type.starts_with("multipart/")
+            && let Some(boundary) = self.header_param("Content-Type", "boundary")
+        {
+            return multipart_parts(raw_body, &boundary)
+                .filter_map(|part| {
+                    // A part without headers is plain text
+                    match part.strip_prefix('\n') {
+                        Some(body) => {
+                            Some(Self {
+                                body: body.to_owned(),
+                                ..Default::default()
+                            })
+                        }
+                        None => Self::parse(part).ok(),
+                    }
+                })
+                .filter(|part| {
+                    part.raw_header("Content-Type")
+                        .is_none_or(|t| t.to_ascii_lowercase().starts_with("text/"))
+                })
+                .map(|part| part.body)
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        let encoding = self
+            .raw_header("Content-Transfer-Encoding")
+            .unwrap_or("8bit")
+            .to_ascii_lowercase();
+        let bytes = match encoding.as_str() {
+            "quoted-printable" => decode_quoted_printable(raw_body, false),
+            "base64" => {
+                let encoded = raw_body
+                    .chars()
+                    .filter(|c| !c.is_ascii_whitespace())
+                    .collect::<String>();
+                match base64::engine::general_purpose::STANDARD.decode(encoded) {
+                    Ok(bytes) => bytes,
+                    Err(err) => {
+                        tracing::warn!("Invalid base64 message body: {err}");
+                        raw_body.as_bytes().to_vec()
+                    }
+                }
+            }
+            _ => return raw_body.to_owned(),
+        };
+
+        decode_charset(
+            &bytes,
+            self.header_param("Content-Type", "charset").as_deref(),
+        )
+    }
+}
+
+impl Diffstat {
+    /// Parses the diffstat of a patch message body. Only the part before the
+    /// first diff is checked.
+    pub fn parse(body: &str) -> Option<Self> {
+        let before_diff = body
+            .split_once("\ndiff --git ")
+            .map_or(body, |(before, _)| before);
+        let summary = DIFFSTAT_SUMMARY_RE.captures_iter(before_diff).last()?;
+        let number = |name: &str| {
+            summary
+                .name(name)
+                .and_then(|m| m.as_str().parse().ok())
+                .unwrap_or_default()
+        };
+
+        let files = before_diff[..summary.get(0).expect("Group zero").start()]
+            .lines()
+            .rev()
+            .take_while(|line| line.starts_with(' ') && line.contains(" | "))
+            .filter_map(|line| {
+                line.split_once(" | ")
+                    .map(|(file, _)| file.trim().to_owned())
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+
+        Some(Self {
+            files,
+            changed: number("files"),
+            insertions: number("insertions"),
+            deletions: number("deletions"),
+        })
+    }
+}
+
+/// Splits an mbox into its messages. A message starts with an mbox `From `
+/// line followed by a header. The mboxrd `>From ` escaping is reverted.
+pub fn split_mbox(mbox: &str) -> Vec<String> {
+    let mut lines = mbox.split('\n').peekable();
+    let mut messages = Vec::new();
+    let mut current = Vec::new();
+
+    while let Some(line) = lines.next() {
+        if !current.is_empty()
+            && FROM_LINE_RE.is_match(line)
+            && lines.peek().is_some_and(|next| next.contains(':'))
+        {
+            messages.push(current.join("\n"));
+            current.clear();
+        }
+        current.push(unescape_from_line(line));
+    }
+
+    if current.iter().any(|line| !line.trim().is_empty()) {
+        messages.push(current.join("\n"));
+    }
+    messages
+}
+
+/// Escapes the lines of the message body that could be read as an mbox `From `
+/// line, using the mboxrd format.
+pub fn escape_from_lines(body: &str) -> String {
+    body.split('\n')
+        .map(|line| {
+            if line.trim_start_matches('>').starts_with("From ") {
+                format!(">{line}")
+            } else {
+                line.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits the headers that `git am` accepts at the beginning of the body from
+/// the rest of the body.
+pub fn in_body_headers(body: &str) -> (Vec<(String, String)>, &str) {
+    let trimmed = body.trim_start_matches(['\n', '\r']);
+    let Some((block, rest)) = trimmed
+        .split_once("\n\n")
+        .or_else(|| trimmed.split_once("\r\n\r\n"))
+    else {
+        return (Vec::new(), body);
+    };
+
+    let headers = block
+        .lines()
+        .map(|line| {
+            line.split_once(':')
+                .filter(|(name, _)| IN_BODY_HEADERS.contains(name))
+                .map(|(name, value)| (name.to_owned(), decode_header(value.trim())))
+        })
+        .collect::<Option<Vec<_>>>();
+
+    match headers {
+        Some(headers) if !headers.is_empty() => (headers, rest),
+        _ => (Vec::new(), body),
+    }
+}
+
+/// Returns the trailers of the commit message, from its last paragraph, e.g.
+/// `Signed-off-by`.
+pub fn parse_trailers(message: &str) -> Vec<(String, String)> {
+    let Some(paragraph) = message
+        .trim_end()
+        .rsplit("\n\n")
+        .next()
+        .filter(|p| !p.trim().is_empty())
+    else {
+        return Vec::new();
+    };
+
+    let mut trailers: Vec<(String, String)> = Vec::new();
+    for line in paragraph.lines() {
+        if line.starts_with([' ', '\t'])
+            && let Some((_, value)) = trailers.last_mut()
+        {
+            value.push(' ');
+            value.push_str(line.trim());
+            continue;
+        }
+
+        let Some(captures) = TRAILER_RE.captures(line) else {
+            return Vec::new();
+        };
+        let token = &captures["token"];
+        if NON_TRAILER_KEYS.contains(&token) {
+            return Vec::new();
+        }
+        trailers.push((token.to_owned(), captures["value"].trim().to_owned()));
+    }
+
+    trailers
+}
+
+/// Decodes the RFC 2047 encoded words of the header value. The whitespace
+/// between two encoded words is removed.
+pub fn decode_header(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut last_end = 0;
+    let mut last_was_encoded = false;
+
+    for captures in ENCODED_WORD_RE.captures_iter(value) {
+        let word = captures.get(0).expect("Group zero");
+        let between = &value[last_end..word.start()];
+        if !(last_was_encoded && between.trim().is_empty()) {
+            decoded.push_str(between);
+        }
+
+        let text = &captures["text"];
+        let bytes = if captures["encoding"].eq_ignore_ascii_case("b") {
+            base64::engine::general_purpose::STANDARD
+                .decode(text)
+                .unwrap_or_else(|_| text.as_bytes().to_vec())
+        } else {
+            decode_quoted_printable(text, true)
+        };
+        decoded.push_str(&decode_charset(&bytes, Some(&captures["charset"])));
+
+        last_end = word.end();
+        last_was_encoded = true;
+    }
+    decoded.push_str(&value[last_end..]);
+
+    decoded
+}
+
+/// Encodes the header value as RFC 2047 encoded words if it's not ASCII.
+pub fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_owned();
+    }
+
+    // An encoded word can't be longer than 75 characters, the value is split
+    // by characters to not break a multibyte character
+    let mut words = vec![String::new()];
+    for c in value.chars() {
+        let word = words.last_mut().expect("Not empty");
+        if word.len() + c.len_utf8() > 45 {
+            words.push(String::new());
+        }
+        words.last_mut().expect("Not empty").push(c);
+    }
+
+    words
+        .iter()
+        .map(|word| {
+            format!(
+                "=?UTF-8?B?{}?=",
+                base64::engine::general_purpose::STANDARD.encode(word)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n ")
+}
+
+/// Formats an address header value, e.g. `Name <email>`. The name is quoted
+/// or encoded if needed.
+pub fn encode_address(name: &str, email: &str) -> String {
+    let name = if name.is_ascii() && name.contains([',', '.', '"', '(', ')', '<', '>', '@', ':']) {
+        format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        encode_header(name)
+    };
+    format!("{name} <{email}>")
+}
+
+/// Returns the message ID without its angle brackets
+pub fn strip_angle_brackets(id: &str) -> &str {
+    id.trim().trim_start_matches('<').trim_end_matches('>')
+}
+
+/// Reverts the mboxrd escaping of the line, `>From ` to `From ` and `>>From `
+/// to `>From `.
+fn unescape_from_line(line: &str) -> &str {
+    match line.strip_prefix('>') {
+        Some(unescaped) if unescaped.trim_start_matches('>').starts_with("From ") => unescaped,
+        _ => line,
+    }
+}
+
+/// Returns the raw parts of a multipart body.
+fn multipart_parts<'a>(body: &'a str, boundary: &str) -> impl Iterator<Item = &'a str> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    let mut part_start = None;
+    let mut offset = 0;
+
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed.starts_with(&delimiter) {
+            if let Some(start) = part_start {
+                // The line break before the delimiter belongs to it
+                parts.push(body[start..offset].trim_end_matches(['\n', '\r']));
+            }
+            if trimmed == format!("{delimiter}--") {
+                break;
+            }
+            part_start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+
+    parts.into_iter()
+}
+
+/// Decodes quoted-printable text. In headers, `_` is a space.
+fn decode_quoted_printable(text: &str, is_header: bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut lines = text.split('\n').peekable();
+
+    while let Some(line) = lines.next() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let (line, soft_break) = match line.trim_end().strip_suffix('=') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+
+        let raw = line.as_bytes();
+        let mut idx = 0;
+        while idx < raw.len() {
+            match raw[idx] {
+                b'=' if raw
+                    .get(idx + 1..idx + 3)
+                    .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit)) =>
+                {
+                    let hex = std::str::from_utf8(&raw[idx + 1..idx + 3]).expect("ASCII hex");
+                    bytes.push(u8::from_str_radix(hex, 16).expect("Valid hex"));
+                    idx += 3;
+                }
+                b'_' if is_header => {
+                    bytes.push(b' ');
+                    idx += 1;
+                }
+                byte => {
+                    bytes.push(byte);
+                    idx += 1;
+                }
+            }
+        }
+
+        if !soft_break && lines.peek().is_some() {
+            bytes.push(b'\n');
+        }
+    }
+
+    bytes
+}
+
+/// Decodes the bytes with the given charset. Only UTF-8, ASCII and Latin-1
+/// are supported, other charsets are decoded as UTF-8 lossily.
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    match charset.map(str::to_ascii_lowercase).as_deref() {
+        Some("iso-8859-1" | "latin1" | "latin-1") => bytes.iter().map(|b| *b as char).collect(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
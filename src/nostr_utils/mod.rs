@@ -19,12 +19,15 @@ pub mod traits;
 /// Utility functions for nostr.
 pub mod utils;
 
-use std::{collections::HashSet, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use futures::future;
 use nostr::{
     event::{Event, EventId, Kind, Tag, TagKind, TagStandard, Tags, UnsignedEvent},
-    filter::Filter,
+    filter::{Alphabet, Filter, SingleLetterTag},
     key::PublicKey,
     nips::{
         nip01::{Coordinate, Metadata},
@@ -33,7 +36,7 @@ use nostr::{
         nip34::GitRepositoryAnnouncement,
     },
     parser::NostrParser,
-    types::RelayUrl,
+    types::{RelayUrl, Timestamp},
 };
 use nostr_sdk::{Client, ClientOptions};
 use traits::TokenUtils;
@@ -41,13 +44,15 @@ use traits::TokenUtils;
 use crate::{
     cli::{CliOptions, issue::IssueStatus, types::PatchPrStatus},
     error::{N34Error, N34Result},
-    nostr_utils::traits::KindExt,
+    nostr_utils::traits::{GitIssuePrMetadata, GitPatchUtils, KindExt},
 };
 
 /// Timeout duration for the client.
 const CLIENT_TIMEOUT: Duration = Duration::from_millis(1500);
 /// Length of a Nostr npub (public key) in characters.
 const NPUB_LEN: usize = 63;
+/// Name of the tag referencing the patch or PR that an event depends on.
+pub const DEPENDS_ON_TAG: &str = "depends-on";
 
 /// Parsed content details
 #[derive(Clone)]
@@ -248,6 +253,44 @@ impl NostrClient {
         .collect()
     }
 
+    /// Returns the commit of the repository `HEAD` branch from the latest state
+    /// announcement of the repository owners or maintainers. `None` if there
+    /// is no state announcement or it doesn't announce the `HEAD` commit.
+    pub async fn fetch_repo_head(
+        &self,
+        coordinates: &[Coordinate],
+        maintainers: &[PublicKey],
+    ) -> N34Result<Option<String>> {
+        let state = self
+            .fetch_events(
+                Filter::new()
+                    .kind(crate::cli::repo::REPO_STATE_KIND)
+                    .identifiers(coordinates.iter().map(|c| c.identifier.clone()))
+                    .authors(utils::dedup(
+                        coordinates
+                            .iter()
+                            .map(|c| c.public_key)
+                            .chain(maintainers.iter().copied()),
+                    )),
+            )
+            .await?
+            .max_by_key(|e| e.created_at);
+
+        Ok(state.and_then(|state| {
+            let head = state
+                .tags
+                .find(crate::cli::repo::HEAD_TAG_KIND)?
+                .content()?
+                .strip_prefix("ref: ")?;
+            tracing::debug!("The repository state `HEAD` is `{head}`");
+            state
+                .tags
+                .find(TagKind::custom(head))?
+                .content()
+                .map(str::to_owned)
+        }))
+    }
+
     /// Fetch the patch by the given id. None if not found
     pub async fn fetch_patch(&self, patch_id: EventId) -> N34Result<Event> {
         self.fetch_event(Filter::new().id(patch_id).kind(Kind::GitPatch))
@@ -262,14 +305,119 @@ impl NostrClient {
             .ok_or(N34Error::CanNotFoundPr)
     }
 
-    /// Returns the username for a given public key. If no username is found,
-    /// falls back to a shortened version of the public key.
-    pub async fn get_username(&self, user: PublicKey) -> String {
+    /// Fetch the patch or the pull request by the given id. None if not found
+    pub async fn fetch_patch_or_pr(&self, event_id: EventId) -> N34Result<Option<Event>> {
+        self.fetch_event(
+            Filter::new()
+                .id(event_id)
+                .kinds([Kind::GitPatch, crate::cli::pr::PR_KIND]),
+        )
+        .await
+    }
+
+    /// Returns the root patches and PRs of the repositories that depend on the
+    /// given patch or PR.
+    pub async fn fetch_dependants(
+        &self,
+        coordinates: &[Coordinate],
+        event_id: EventId,
+    ) -> N34Result<Vec<Event>> {
+        Ok(self
+            .fetch_events(
+                Filter::new()
+                    .coordinates(coordinates.iter())
+                    .kinds([Kind::GitPatch, crate::cli::pr::PR_KIND]),
+            )
+            .await?
+            .filter(|e| e.depends_on().contains(&event_id))
+            .collect())
+    }
+
+    /// Returns the updates of the PR published by the PR author, sorted from
+    /// the oldest.
+    pub async fn fetch_pr_updates(&self, pr: &Event) -> N34Result<Vec<Event>> {
+        let mut updates = self
+            .fetch_events(
+                Filter::new()
+                    .kind(crate::cli::pr::PR_UPDATE_KIND)
+                    .custom_tag(SingleLetterTag::uppercase(Alphabet::E), pr.id)
+                    .author(pr.pubkey),
+            )
+            .await?
+            .collect::<Vec<_>>();
+        updates.sort_by_key(|e| e.created_at);
+        Ok(updates)
+    }
+
+    /// Returns the latest update of the PR, published by the PR author. `None`
+    /// if the PR is not updated.
+    pub async fn fetch_pr_update(&self, pr: &Event) -> N34Result<Option<Event>> {
+        Ok(self.fetch_pr_updates(pr).await?.pop())
+    }
+
+    /// Returns the time of the latest activity on each of the events, the
+    /// latest event referencing it by an `e` or `E` tag, like its statuses,
+    /// comments, labels and updates. The event creation time if there is no
+    /// activity. The activities of all the events are fetched at once.
+    pub async fn fetch_last_activities(
+        &self,
+        events: &[&Event],
+    ) -> N34Result<HashMap<EventId, Timestamp>> {
+        let mut activities = events
+            .iter()
+            .map(|e| (e.id, e.created_at))
+            .collect::<HashMap<_, _>>();
+        if events.is_empty() {
+            return Ok(activities);
+        }
+
+        let ids = events.iter().map(|e| e.id);
+        let (replies, roots) = future::join(
+            self.fetch_events(Filter::new().events(ids.clone())),
+            self.fetch_events(Filter::new().custom_tags(
+                SingleLetterTag::uppercase(Alphabet::E),
+                ids.map(|id| id.to_hex()),
+            )),
+        )
+        .await;
+
+        for reply in replies?.chain(roots?) {
+            let referenced = reply.tags.iter().filter_map(|tag| {
+                matches!(
+                    tag.kind(),
+                    TagKind::SingleLetter(SingleLetterTag {
+                        character: Alphabet::E,
+                        ..
+                    })
+                )
+                .then(|| EventId::from_hex(tag.content()?).ok())
+                .flatten()
+            });
+            for id in referenced {
+                if let Some(activity) = activities.get_mut(&id) {
+                    *activity = (*activity).max(reply.created_at);
+                }
+            }
+        }
+
+        Ok(activities)
+    }
+
+    /// Returns the metadata of the given user, `None` if it's not found or
+    /// invalid.
+    pub async fn fetch_metadata(&self, user: PublicKey) -> Option<Metadata> {
         self.fetch_event(Filter::new().kind(Kind::Metadata).author(user))
             .await
             .ok()
             .flatten()
             .and_then(|e| Metadata::try_from(&e).ok())
+    }
+
+    /// Returns the username for a given public key. If no username is found,
+    /// falls back to a shortened version of the public key.
+    pub async fn get_username(&self, user: PublicKey) -> String {
+        self.fetch_metadata(user)
+            .await
             .and_then(|m| m.display_name.or(m.name))
             .unwrap_or_else(|| {
                 let pubkey = user.to_bech32().expect("The error is `Infallible`");
@@ -277,6 +425,17 @@ impl NostrClient {
             })
     }
 
+    /// Returns the usernames of the given users, fetched concurrently. See
+    /// [`NostrClient::get_username`].
+    pub async fn get_usernames(
+        &self,
+        users: impl Iterator<Item = PublicKey>,
+    ) -> HashMap<PublicKey, String> {
+        let users = utils::dedup(users);
+        let names = future::join_all(users.iter().map(|u| self.get_username(*u))).await;
+        users.into_iter().zip(names).collect()
+    }
+
     /// Get the latest status of an issue by its ID, only considering status
     /// events from authorized_pubkeys. If no valid status event is found,
     /// defaults to Open.
@@ -296,6 +455,26 @@ impl NostrClient {
         .unwrap_or_else(|| Ok(IssueStatus::Open))
     }
 
+    /// Returns the NIP-32 label events of the issue or PR, only considering
+    /// the events of `authorized_pubkeys`. Sorted from the oldest.
+    pub async fn fetch_label_events(
+        &self,
+        event_id: EventId,
+        authorized_pubkeys: Vec<PublicKey>,
+    ) -> N34Result<Vec<Event>> {
+        let mut events = self
+            .fetch_events(
+                Filter::new()
+                    .event(event_id)
+                    .kind(Kind::Label)
+                    .authors(utils::dedup(authorized_pubkeys.into_iter())),
+            )
+            .await?
+            .collect::<Vec<_>>();
+        events.sort_by_key(|e| e.created_at);
+        Ok(events)
+    }
+
     /// Get the latest status of PR by its ID, only considering status
     /// events from authorized_pubkeys. If no valid status event is found,
     /// defaults to Open.
@@ -304,15 +483,31 @@ impl NostrClient {
         pr_id: EventId,
         authorized_pubkeys: Vec<PublicKey>,
     ) -> N34Result<PatchPrStatus> {
-        self.fetch_event(
-            Filter::new()
-                .event(pr_id)
-                .kinds(PatchPrStatus::all_kinds())
-                .authors(utils::dedup(authorized_pubkeys.into_iter())),
-        )
-        .await?
-        .map(|status| PatchPrStatus::try_from(status.kind))
-        .unwrap_or_else(|| Ok(PatchPrStatus::Open))
+        self.fetch_pr_status_events(pr_id, authorized_pubkeys)
+            .await?
+            .pop()
+            .map(|status| PatchPrStatus::try_from(status.kind))
+            .unwrap_or_else(|| Ok(PatchPrStatus::Open))
+    }
+
+    /// Returns the status events of the PR, only the ones published by
+    /// authorized_pubkeys. Sorted from the oldest.
+    pub async fn fetch_pr_status_events(
+        &self,
+        pr_id: EventId,
+        authorized_pubkeys: Vec<PublicKey>,
+    ) -> N34Result<Vec<Event>> {
+        let mut statuses = self
+            .fetch_events(
+                Filter::new()
+                    .event(pr_id)
+                    .kinds(PatchPrStatus::all_kinds())
+                    .authors(utils::dedup(authorized_pubkeys.into_iter())),
+            )
+            .await?
+            .collect::<Vec<_>>();
+        statuses.sort_by_key(|e| e.created_at);
+        Ok(statuses)
     }
 
     /// Gets the status of a patch. If it's a revision patch, checks if it's
@@ -369,6 +564,43 @@ impl NostrClient {
             .collect())
     }
 
+    /// Fetches the patch series of the given root patch, ordered by following
+    /// the `e`-reply chain starting from the root. The root is the first
+    /// element of the returned vector.
+    pub async fn fetch_ordered_patch_series(&self, root_patch: Event) -> N34Result<Vec<Event>> {
+        let series = self
+            .fetch_patch_series(root_patch.id, root_patch.pubkey)
+            .await?;
+        Ok(utils::order_patch_series(root_patch, series))
+    }
+
+    /// Fetches the revisions of the given root patch, the root patches that
+    /// reply to it and marked as a revision. Sorted from the oldest.
+    pub async fn fetch_patch_revisions(&self, root_patch_id: EventId) -> N34Result<Vec<Event>> {
+        let mut revisions = self
+            .fetch_events(Filter::new().kind(Kind::GitPatch).event(root_patch_id))
+            .await?
+            .filter(|e| e.is_revision_patch() && e.patch_reply_to() == Some(root_patch_id))
+            .collect::<Vec<_>>();
+        revisions.sort_by_key(|e| e.created_at);
+        Ok(revisions)
+    }
+
+    /// Fetches the NIP-22 comments under the given root event. Sorted from the
+    /// oldest.
+    pub async fn fetch_comments(&self, root_id: EventId) -> N34Result<Vec<Event>> {
+        let mut comments = self
+            .fetch_events(
+                Filter::new()
+                    .kind(Kind::Comment)
+                    .custom_tag(SingleLetterTag::uppercase(Alphabet::E), root_id),
+            )
+            .await?
+            .collect::<Vec<_>>();
+        comments.sort_by_key(|e| e.created_at);
+        Ok(comments)
+    }
+
     /// Finds the root issue, PR or patch for a given event. If the event is
     /// already a root (issue/patch/PR), returns it directly. For comments,
     /// follows parent/root references until finding the root or failing.
@@ -15,12 +15,14 @@
 // along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
 
 use std::ops::Deref;
+use std::str::FromStr;
 
 use convert_case::{Case, Casing};
 use nostr::hashes::sha1::Hash as Sha1Hash;
 use nostr::nips::nip19::FromBech32;
 use nostr::{
     event::{Event, EventBuilder, EventId, Kind, Tag, TagKind, TagStandard, Tags},
+    filter::Alphabet,
     key::PublicKey,
     nips::{
         nip01::Coordinate,
@@ -39,6 +41,7 @@ use crate::cli::patch::{
     ROOT_HASHTAG_CONTENT,
 };
 use crate::error::{N34Error, N34Result};
+use crate::git_utils::Committer;
 
 
 /// A trait to add helper instance function to [`Tags`] type
@@ -334,6 +337,68 @@ impl Event {
             })
     }
 
+    /// Returns the ID of the event that this patch replies to, taken from its
+    /// `e`-reply tag.
+    #[inline]
+    pub fn patch_reply_to(&self) -> Option<EventId> {
+        self.tags
+            .iter()
+            .find(|tag| tag.is_reply())
+            .and_then(|tag| tag.content())
+            .and_then(|id| id.parse().ok())
+    }
+
+    /// Returns the ID of the root patch of this series, taken from its
+    /// `e`-root tag. `None` for the root patch itself.
+    #[inline]
+    pub fn patch_root(&self) -> Option<EventId> {
+        self.tags
+            .iter()
+            .find(|tag| tag.is_root())
+            .and_then(|tag| tag.content())
+            .and_then(|id| id.parse().ok())
+    }
+
+    /// Returns the value of the given custom tag of the patch, e.g. `commit`.
+    #[inline]
+    pub fn patch_tag_value(&self, kind: &str) -> Option<&str> {
+        self.tags
+            .find(TagKind::custom(kind))
+            .and_then(|tag| tag.content())
+    }
+
+    /// Returns the patch commit from its `commit` tag
+    #[inline]
+    pub fn patch_commit(&self) -> Option<&str> {
+        self.patch_tag_value("commit")
+    }
+
+    /// Returns the patch parent commit from its `parent-commit` tag
+    #[inline]
+    pub fn patch_parent_commit(&self) -> Option<&str> {
+        self.patch_tag_value("parent-commit")
+    }
+
+    /// Returns the commit PGP signature from the `commit-pgp-sig` tag. `None`
+    /// if the tag is missing or empty, which means the commit is not signed.
+    #[inline]
+    pub fn patch_pgp_sig(&self) -> Option<&str> {
+        self.patch_tag_value("commit-pgp-sig")
+            .filter(|sig| !sig.is_empty())
+    }
+
+    /// Returns the commit committer from the `committer` tag, which contains
+    /// the name, email, timestamp and the timezone offset in minutes.
+    pub fn patch_committer(&self) -> Option<Committer> {
+        let tag = self.tags.find(TagKind::custom("committer"))?.as_slice();
+        Some(Committer {
+            name:      tag.get(1)?.clone(),
+            email:     tag.get(2)?.clone(),
+            timestamp: tag.get(3)?.parse().ok()?,
+            offset:    tag.get(4)?.parse().ok()?,
+        })
+    }
+
     /// Gets the root patch ID from a patch-revision event by finding the `e`
     /// tag that replies to it. Fails if no such tag is found or if the tag
     /// contains an invalid event ID.
@@ -375,6 +440,47 @@ impl Event {
             .collect::<Vec<_>>()
             .join(", ")
     }
+
+    /// Returns the commit at the tip of the PR from its `c` tag. For a PR
+    /// update, it's the new tip. A tag that is not a SHA-1 hash is ignored.
+    #[inline]
+    pub fn pr_commit(&self) -> Option<&str> {
+        self.tags
+            .find(TagKind::single_letter(Alphabet::C, false))
+            .and_then(|t| t.content())
+            .filter(|commit| Sha1Hash::from_str(commit).is_ok())
+    }
+
+    /// Returns the branch name of the PR from its `branch-name` tag
+    #[inline]
+    pub fn pr_branch_name(&self) -> Option<&str> {
+        self.tags
+            .find(TagKind::custom("branch-name"))
+            .and_then(|t| t.content())
+    }
+
+    /// Returns the patches and PRs that this patch or PR depends on, from its
+    /// `depends-on` tags
+    pub fn depends_on(&self) -> Vec<EventId> {
+        self.tags
+            .filter(TagKind::custom(super::DEPENDS_ON_TAG))
+            .filter_map(|t| t.content().and_then(|id| EventId::from_hex(id).ok()))
+            .collect()
+    }
+
+    /// Returns the clone URLs of the PR from its `clone` tag
+    pub fn pr_clones(&self) -> Vec<Url> {
+        self.tags
+            .iter()
+            .filter_map(|t| t.as_standardized())
+            .find_map(|t| {
+                match t {
+                    TagStandard::GitClone(urls) if !urls.is_empty() => Some(urls.clone()),
+                    _ => None,
+                }
+            })
+            .unwrap_or_default()
+    }
 }
 
 #[easy_ext::ext(KindExt)]
@@ -14,6 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://gnu.org/licenses/gpl-3.0.html>.
 
+#[cfg(test)]
+mod tests;
+
 use std::{
     fmt,
     fs,
@@ -34,14 +37,15 @@ use nostr::{
         nip34::GitRepositoryAnnouncement,
         nip65::{self, RelayMetadata},
     },
-    types::RelayUrl,
+    types::{RelayUrl, Timestamp},
 };
 
-use super::traits::TagsExt;
+use super::traits::{GitPatchUtils, TagsExt};
 use crate::{
-    cli::{NOSTR_ADDRESS_FILE, parsers, utils as cli_utils},
+    cli::{NOSTR_ADDRESS_FILE, parsers},
     error::{N34Error, N34Result},
-    nostr_utils::traits::ReposUtils,
+    git_utils,
+    nostr_utils::{DEPENDS_ON_TAG, traits::ReposUtils},
 };
 
 /// Returns the value of the given tag
@@ -128,6 +132,37 @@ where
     vector.into_iter()
 }
 
+/// Orders the patch series by following the `e`-reply chain starting from the
+/// root patch. Patches that are not part of the chain are appended at the end
+/// sorted by their creation date.
+pub fn order_patch_series(root_patch: Event, series: Vec<Event>) -> Vec<Event> {
+    let mut series = sort_by_key(series, |e| e.created_at).collect::<Vec<_>>();
+    series.dedup_by_key(|e| e.id);
+    series.retain(|e| e.id != root_patch.id);
+
+    let mut ordered = vec![root_patch];
+    loop {
+        let previous = ordered.last().expect("There is the root").id;
+        let Some(position) = series
+            .iter()
+            .position(|e| e.patch_reply_to() == Some(previous))
+        else {
+            break;
+        };
+        ordered.push(series.remove(position));
+    }
+
+    if !series.is_empty() {
+        tracing::warn!(
+            "{} patch(es) are not part of the reply chain, appending them by creation date",
+            series.len()
+        );
+        ordered.extend(series);
+    }
+
+    ordered
+}
+
 /// Creates a new NIP-19 nevent string from an event ID and up to 3 unique relay
 /// URLs.
 #[inline]
@@ -278,6 +313,17 @@ pub fn event_reply_tag(reply_to: &EventId, relay: Option<&RelayUrl>, marker: Mar
     )
 }
 
+/// Returns a `depends-on` tag referencing the patch or PR the event depends on.
+pub fn depends_on_tag(depends_on: &EventId, relay: Option<&RelayUrl>) -> Tag {
+    Tag::custom(
+        TagKind::custom(DEPENDS_ON_TAG),
+        [
+            depends_on.to_hex(),
+            relay.map(|r| r.to_string()).unwrap_or_default(),
+        ],
+    )
+}
+
 /// Wraps text into lines no longer than max_width, breaking only at whitespace.
 pub fn smart_wrap(text: &str, max_width: usize) -> String {
     text.lines()
@@ -328,7 +374,16 @@ pub fn subject_and_body(
     }
 
     // There is no subject, so we need to get it from the editor
-    let file_content = read_editor(None, file_suffix)?;
+    editor_subject_and_body(None, file_suffix)
+}
+
+/// Opens the editor with the given pre content, and returns the first line of
+/// the edited file as the subject and the rest as the body.
+pub fn editor_subject_and_body(
+    file_pre_content: Option<&str>,
+    file_suffix: &str,
+) -> N34Result<(String, Option<String>)> {
+    let file_content = read_editor(file_pre_content, file_suffix)?;
     // if there is a subject and body
     if file_content.contains('\n') {
         Ok(file_content
@@ -354,17 +409,47 @@ where
         .join("\n")
 }
 
+/// Formats the timestamp as a UTC date, e.g. `2025-06-03 08:41 UTC`
+pub fn format_date(timestamp: Timestamp) -> String {
+    chrono::DateTime::from_timestamp(timestamp.as_u64().try_into().unwrap_or(i64::MAX), 0)
+        .map(|datetime| datetime.format("%F %R UTC").to_string())
+        .unwrap_or_else(|| "N/A".to_owned())
+}
+
+/// Formats the timestamp relative to `now`, e.g. `3 days ago`
+pub fn format_relative_date(timestamp: Timestamp, now: Timestamp) -> String {
+    const UNITS: [(u64, &str); 6] = [
+        (365 * 24 * 60 * 60, "year"),
+        (30 * 24 * 60 * 60, "month"),
+        (7 * 24 * 60 * 60, "week"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ];
+
+    let elapsed = now.as_u64().saturating_sub(timestamp.as_u64());
+    UNITS
+        .iter()
+        .find(|(secs, _)| elapsed >= *secs)
+        .map(|(secs, unit)| {
+            let count = elapsed / secs;
+            format!("{count} {unit}{} ago", if count == 1 { "" } else { "s" })
+        })
+        .unwrap_or_else(|| "just now".to_owned())
+}
+
 /// Creates a grasp event by extracting grasp servers from the provided
 /// repositories. If no grasp servers are found, an error is returned. If one or
-/// more are found, the user is prompted to push to each server until one is
-/// successful. The successful event is returned, or an error if no push
-/// succeeds.
+/// more are found, the commit is pushed to `refs/nostr/<event-id>` of each
+/// server until one accepts it. The event of the accepting server is returned,
+/// or an error if all of them reject the push.
 pub fn build_grasp_event(
     repos: &Vec<GitRepositoryAnnouncement>,
     author_pubkey: PublicKey,
     event_builder: EventBuilder,
+    commit: &str,
+    interactive: bool,
 ) -> N34Result<UnsignedEvent> {
-    let mut event = None;
     let grasp_repos = repos.extract_grasp_servers();
     tracing::debug!("GRASP repos: {grasp_repos:?}");
 
@@ -384,17 +469,13 @@ pub fn build_grasp_event(
                 iter::once(grasp_repo.to_string()),
             ))
             .build(author_pubkey);
+        let reference = format!("refs/nostr/{}", grasp_event.id());
 
-        println!(
-            "Attempt to push your changes to `refs/nostr/{}` in the repository: {}",
-            grasp_event.id(),
-            grasp_repo
-        );
-
-        if cli_utils::prompt_bool("Was the push successful? (yes/no)")? {
-            event = Some(grasp_event);
-            break;
+        println!("Pushing your changes to `{reference}` in the repository: {grasp_repo}");
+        match git_utils::push(grasp_repo.as_str(), commit, &reference, interactive) {
+            Ok(()) => return Ok(grasp_event),
+            Err(err) => tracing::warn!("The push to `{grasp_repo}` is rejected: {err}"),
         }
     }
-    event.ok_or(N34Error::PushRejectedByGraspServers)
+    Err(N34Error::PushRejectedByGraspServers)
 }